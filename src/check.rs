@@ -0,0 +1,160 @@
+use crate::mission::Mission;
+use crate::rover::{
+    heading_name, parse_heading, simulate_rovers_until_failure, PositionAndHeading, RoverError,
+    SimulationResult,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Outcome {
+    Finished(PositionAndHeading),
+    Failed(String), // The name of the error the rover stopped with, e.g. 'Collision'
+    NotDeployed,    // An earlier rover failed, so the mission ended before this one
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoverCheck {
+    pub rover: usize, // Counting from 0 in the order rovers are deployed
+    pub expected: Outcome,
+    pub actual: Outcome,
+}
+
+pub fn error_name(err: &RoverError) -> String {
+    // Only the variant is compared, not any line numbers or names it carries
+    format!("{:?}", err)
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub fn parse_outcome(outcome: &str) -> Option<Outcome> {
+    // Outcomes are given as a final position, e.g. '1 3 N', or an error, e.g. 'error Collision'
+    match outcome.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["error", name] => Some(Outcome::Failed(name.to_string())),
+        [x, y, heading] => Some(Outcome::Finished(PositionAndHeading {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            heading: parse_heading(heading)?,
+        })),
+        _ => None,
+    }
+}
+
+pub fn rover_outcomes(mission: &Mission) -> Result<Vec<Outcome>, RoverError> {
+    // A mission that can't be run, e.g. one with an invalid program, is an error
+    // rather than an outcome of any rover
+    let rover_count = mission.input_command.rovers_to_deploy.len();
+    let result = simulate_rovers_until_failure(mission.input_command.clone(), &mission.options)?;
+    Ok(simulation_outcomes(&result, rover_count))
+}
+
+pub fn simulation_outcomes(result: &SimulationResult, rover_count: usize) -> Vec<Outcome> {
+    let mut outcomes = result
+        .reports
+        .iter()
+        .map(|report| Outcome::Finished(report.trajectory[report.trajectory.len() - 1].clone()))
+        .collect::<Vec<_>>();
+    if let Some(failure) = &result.failure {
+        outcomes.push(Outcome::Failed(error_name(&failure.error)));
+    }
+    outcomes.resize(rover_count, Outcome::NotDeployed);
+    outcomes
+}
+
+fn deploy_first(mission: &Mission, count: usize) -> Result<SimulationResult, RoverError> {
+    let mut input_command = mission.input_command.clone();
+    input_command.rovers_to_deploy.truncate(count);
+    simulate_rovers_until_failure(input_command, &mission.options)
+}
+
+fn checked_outcomes(mission: &Mission) -> Result<Vec<Outcome>, RoverError> {
+    // A rover whose start or program can't be read stops the mission with that error,
+    // the same as one failing on the plateau, so it can be expected. The rovers before
+    // it are run on their own to find out what they did
+    let rover_count = mission.input_command.rovers_to_deploy.len();
+    let err = match rover_outcomes(mission) {
+        Ok(outcomes) => return Ok(outcomes),
+        Err(err) => err,
+    };
+    let unreadable = (1..=rover_count)
+        .map(|count| (count - 1, deploy_first(mission, count)))
+        .find_map(|(rover, result)| result.err().map(|err| (rover, err)));
+    let (rover, err) = match unreadable {
+        Some(unreadable) => unreadable,
+        None => return Err(err),
+    };
+    let result = deploy_first(mission, rover)?;
+    let mut outcomes = simulation_outcomes(&result, rover);
+    if result.failure.is_none() {
+        outcomes.push(Outcome::Failed(error_name(&err)));
+    }
+    outcomes.resize(rover_count, Outcome::NotDeployed);
+    Ok(outcomes)
+}
+
+pub fn check_mission(mission: &Mission) -> Result<Vec<RoverCheck>, RoverError> {
+    // Rovers without an expected outcome are expected to never be deployed
+    Ok(checked_outcomes(mission)?
+        .into_iter()
+        .enumerate()
+        .map(|(rover, actual)| RoverCheck {
+            rover,
+            expected: mission
+                .expectations
+                .get(rover)
+                .cloned()
+                .unwrap_or(Outcome::NotDeployed),
+            actual,
+        })
+        .collect())
+}
+
+pub fn format_outcome(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Finished(position) => {
+            format!(
+                "{} {} {}",
+                position.x,
+                position.y,
+                heading_name(position.heading)
+            )
+        }
+        Outcome::Failed(name) => format!("error {}", name),
+        Outcome::NotDeployed => "not deployed".to_string(),
+    }
+}
+
+pub fn format_check_report(checks: &[RoverCheck]) -> String {
+    let mut formatted = String::new();
+    for check in checks {
+        if check.expected == check.actual {
+            formatted.push_str(&format!(
+                "Rover {}: ok ({})\n",
+                check.rover + 1,
+                format_outcome(&check.actual)
+            ));
+        } else {
+            formatted.push_str(&format!(
+                "Rover {}: expected {}, got {}\n",
+                check.rover + 1,
+                format_outcome(&check.expected),
+                format_outcome(&check.actual)
+            ));
+        }
+    }
+    let passed = checks
+        .iter()
+        .filter(|check| check.expected == check.actual)
+        .count();
+    formatted.push_str(&format!(
+        "\n{}/{} rover(s) as expected\n",
+        passed,
+        checks.len()
+    ));
+    formatted
+}
+
+pub fn print_check_report(checks: &[RoverCheck]) {
+    println!("\nExpected outcome check:\n");
+    print!("{}", format_check_report(checks));
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::rover::{PositionAndHeading, RoverError};
+
+// Commands every rover understands, which can't be replaced by custom ones
+pub const BUILT_IN_COMMANDS: [char; 6] = ['L', 'R', 'M', 'B', 'U', 'S'];
+
+// A command letter added to the rover programs, e.g. 'D' to drill or 'P' to take a photo.
+// Commands are shared between threads along with the mission options they're registered on
+pub trait Command: Send + Sync {
+    // The uppercase letter invoking the command in a program
+    fn letter(&self) -> char;
+
+    // Carries out the command for the rover deployed at `rover` (counting from 0),
+    // returning its position and heading afterwards. The simulator checks any new
+    // position and heading the same way it checks moves and turns
+    fn apply(
+        &self,
+        rover: usize,
+        position_and_heading: &PositionAndHeading,
+    ) -> Result<PositionAndHeading, RoverError>;
+}
+
+#[derive(Clone, Default)]
+pub struct CommandRegistry {
+    commands: Vec<Arc<dyn Command>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        CommandRegistry::default()
+    }
+
+    pub fn register<C: Command + 'static>(&mut self, command: C) -> Result<(), RoverError> {
+        // Program letters are uppercased before they're read, so only those can be reached
+        let letter = command.letter();
+        if !letter.is_ascii_uppercase()
+            || BUILT_IN_COMMANDS.contains(&letter)
+            || self.get(letter).is_some()
+        {
+            return Err(RoverError::InvalidCommand(letter));
+        }
+        self.commands.push(Arc::new(command));
+        Ok(())
+    }
+
+    pub fn get(&self, letter: char) -> Option<&dyn Command> {
+        self.commands
+            .iter()
+            .find(|command| command.letter() == letter)
+            .map(|command| command.as_ref())
+    }
+
+    pub fn letters(&self) -> Vec<char> {
+        self.commands
+            .iter()
+            .map(|command| command.letter())
+            .collect()
+    }
+}
+
+// Registries are told apart by the letters they hold, as commands can't be compared
+impl fmt::Debug for CommandRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CommandRegistry")
+            .field("letters", &self.letters())
+            .finish()
+    }
+}
+
+impl PartialEq for CommandRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.letters() == other.letters()
+    }
+}
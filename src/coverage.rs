@@ -0,0 +1,145 @@
+use crate::rover::{
+    trace_rover_moves_with_options, InputCommand, MissionOptions, PositionAndHeading, RoverError,
+    Topology, NORTH_EAST, NORTH_WEST, SOUTH_EAST, SOUTH_WEST,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Camera {
+    pub range: i32,         // Furthest distance (in cells) the camera can resolve
+    pub field_of_view: i32, // Full viewing angle in degrees, centered on the rover's heading
+}
+
+pub const DEFAULT_CAMERA: Camera = Camera {
+    range: 2,
+    field_of_view: 90,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct CoverageMap {
+    pub ur_plateau: (i32, i32), // Upper right plateau coordinates
+    // Cells indexed as observed[y][x], with y = 0 being the southern edge
+    pub observed: Vec<Vec<bool>>,
+}
+
+fn heading_vector(heading: char) -> Option<(i32, i32)> {
+    match heading {
+        'N' => Some((0, 1)),
+        NORTH_EAST => Some((1, 1)),
+        'E' => Some((1, 0)),
+        SOUTH_EAST => Some((1, -1)),
+        'S' => Some((0, -1)),
+        SOUTH_WEST => Some((-1, -1)),
+        'W' => Some((-1, 0)),
+        NORTH_WEST => Some((-1, 1)),
+        _ => None,
+    }
+}
+
+pub fn is_cell_in_view(position: &PositionAndHeading, camera: &Camera, cell: (i32, i32)) -> bool {
+    let offset = (cell.0 - position.x, cell.1 - position.y);
+    // The rover always sees the cell it is standing on
+    if offset == (0, 0) {
+        return true;
+    }
+    if offset.0 * offset.0 + offset.1 * offset.1 > camera.range * camera.range {
+        return false;
+    }
+
+    let facing = match heading_vector(position.heading) {
+        Some(facing) => facing,
+        None => return false,
+    };
+    // Compare the angle between the heading and the cell against half the field of view
+    let distance = ((offset.0 * offset.0 + offset.1 * offset.1) as f64).sqrt();
+    let facing_length = ((facing.0 * facing.0 + facing.1 * facing.1) as f64).sqrt();
+    let cos_angle = (offset.0 * facing.0 + offset.1 * facing.1) as f64 / (distance * facing_length);
+    let angle = cos_angle.clamp(-1.0, 1.0).acos().to_degrees();
+
+    // Allow a little slack so cells exactly on the edge of the view are counted
+    angle <= camera.field_of_view as f64 / 2.0 + 1e-9
+}
+
+pub fn map_coverage(
+    ur_plateau: (i32, i32),
+    trajectories: &[Vec<PositionAndHeading>],
+    cameras: &[Camera],
+) -> CoverageMap {
+    let mut observed =
+        vec![vec![false; (ur_plateau.0 + 1).max(0) as usize]; (ur_plateau.1 + 1).max(0) as usize];
+
+    // Rovers without a camera of their own fall back to the default one
+    for (index, trajectory) in trajectories.iter().enumerate() {
+        let camera = cameras.get(index).unwrap_or(&DEFAULT_CAMERA);
+
+        for position in trajectory {
+            for (y, row) in observed.iter_mut().enumerate() {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    if !*cell && is_cell_in_view(position, camera, (x as i32, y as i32)) {
+                        *cell = true;
+                    }
+                }
+            }
+        }
+    }
+
+    CoverageMap {
+        ur_plateau,
+        observed,
+    }
+}
+
+pub fn map_rover_coverage(
+    input_command: InputCommand,
+    options: &MissionOptions,
+    cameras: &[Camera],
+) -> Result<CoverageMap, RoverError> {
+    // Cameras are modelled on square cells, so hex plateaus have no coverage map
+    if options.topology == Topology::Hex {
+        return Err(RoverError::InvalidTopology);
+    }
+    let ur_plateau = input_command.ur_plateau;
+    let trajectories = trace_rover_moves_with_options(input_command, options)?;
+    Ok(map_coverage(ur_plateau, &trajectories, cameras))
+}
+
+pub fn coverage_percentage(coverage: &CoverageMap) -> f64 {
+    let total_cells = coverage.observed.iter().map(|row| row.len()).sum::<usize>();
+    let observed_cells = coverage
+        .observed
+        .iter()
+        .flatten()
+        .filter(|observed| **observed)
+        .count();
+
+    if total_cells == 0 {
+        0.0
+    } else {
+        observed_cells as f64 * 100.0 / total_cells as f64
+    }
+}
+
+pub fn format_coverage_map(coverage: &CoverageMap) -> String {
+    // Draw north at the top, so rows are printed from the highest y down
+    let mut output = String::new();
+    for row in coverage.observed.iter().rev() {
+        let line = row
+            .iter()
+            .map(|observed| if *observed { '#' } else { '.' })
+            .collect::<String>();
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+pub fn format_coverage_report(coverage: &CoverageMap) -> String {
+    format!(
+        "\nCamera coverage ('#' observed, '.' never photographed):\n\n{}\nCoverage: {:.1}%\n",
+        format_coverage_map(coverage),
+        coverage_percentage(coverage)
+    )
+}
+
+pub fn print_coverage_map(coverage: &CoverageMap) {
+    print!("{}", format_coverage_report(coverage));
+}
@@ -0,0 +1,49 @@
+// Every state something has been in, so any change can be undone and redone.
+// Recording a new state after undoing drops the states that could have been redone
+#[derive(Debug, PartialEq, Clone)]
+pub struct History<T> {
+    states: Vec<T>,
+    current: usize, // Index of the current state in states
+}
+
+impl<T> History<T> {
+    pub fn new(initial: T) -> History<T> {
+        History {
+            states: vec![initial],
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &T {
+        &self.states[self.current]
+    }
+
+    pub fn record(&mut self, state: T) {
+        self.states.truncate(self.current + 1);
+        self.states.push(state);
+        self.current += 1;
+    }
+
+    // Both return the state moved to, or None when there's nothing to undo or redo
+    pub fn undo(&mut self) -> Option<&T> {
+        if self.current == 0 {
+            return None;
+        }
+        self.current -= 1;
+        Some(self.current())
+    }
+
+    pub fn redo(&mut self) -> Option<&T> {
+        if self.current + 1 == self.states.len() {
+            return None;
+        }
+        self.current += 1;
+        Some(self.current())
+    }
+}
+
+impl<T: Default> Default for History<T> {
+    fn default() -> Self {
+        History::new(T::default())
+    }
+}
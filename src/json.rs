@@ -0,0 +1,296 @@
+use crate::rover::RoverError;
+
+// Nesting deeper than this is rejected rather than risking the stack
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>), // Members kept in the order they were written
+}
+
+impl JsonValue {
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            JsonValue::Number(n)
+                if n.fract() == 0.0 && *n >= i32::MIN as f64 && *n <= i32::MAX as f64 =>
+            {
+                Some(*n as i32)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_i32()
+            .and_then(|n| if n >= 0 { Some(n as usize) } else { None })
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize, // Position of the next character to read
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> RoverError {
+        // Report where parsing stopped as a character offset into the text
+        RoverError::InvalidJson(format!("{} at character {}", message, self.index))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.get(self.index) {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.index += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.index).cloned()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), RoverError> {
+        if self.peek() == Some(expected) {
+            self.index += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, RoverError> {
+        let end = self.index + literal.len();
+        if end <= self.chars.len()
+            && self.chars[self.index..end]
+                .iter()
+                .copied()
+                .eq(literal.chars())
+        {
+            self.index = end;
+            Ok(value)
+        } else {
+            Err(self.error("unexpected value"))
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, RoverError> {
+        let start = self.index;
+        while let Some(c) = self.chars.get(self.index) {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+        let number = self.chars[start..self.index].iter().collect::<String>();
+        number
+            .parse()
+            .map(JsonValue::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, RoverError> {
+        let end = self.index + 4;
+        if end > self.chars.len() {
+            return Err(self.error("invalid unicode escape"));
+        }
+        let hex = self.chars[self.index..end].iter().collect::<String>();
+        self.index = end;
+        u32::from_str_radix(&hex, 16).map_err(|_| self.error("invalid unicode escape"))
+    }
+
+    fn parse_string(&mut self) -> Result<String, RoverError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            let c = match self.chars.get(self.index) {
+                Some(c) => *c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.index += 1;
+            match c {
+                '"' => return Ok(string),
+                '\\' => {
+                    let escaped = self.chars.get(self.index).cloned();
+                    self.index += 1;
+                    match escaped {
+                        Some('"') => string.push('"'),
+                        Some('\\') => string.push('\\'),
+                        Some('/') => string.push('/'),
+                        Some('b') => string.push('\u{8}'),
+                        Some('f') => string.push('\u{c}'),
+                        Some('n') => string.push('\n'),
+                        Some('r') => string.push('\r'),
+                        Some('t') => string.push('\t'),
+                        Some('u') => {
+                            let mut code = self.parse_hex_escape()?;
+                            // Characters outside the basic plane come as a surrogate pair
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.chars.get(self.index) == Some(&'\\')
+                                && self.chars.get(self.index + 1) == Some(&'u')
+                            {
+                                self.index += 2;
+                                let low = self.parse_hex_escape()?;
+                                code =
+                                    0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00));
+                            }
+                            match char::from_u32(code) {
+                                Some(c) => string.push(c),
+                                None => return Err(self.error("invalid unicode escape")),
+                            }
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<JsonValue, RoverError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        match self.peek() {
+            Some('{') => {
+                self.index += 1;
+                let mut members = Vec::new();
+                if self.peek() == Some('}') {
+                    self.index += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                loop {
+                    let name = self.parse_string()?;
+                    self.expect(':')?;
+                    members.push((name, self.parse_value(depth + 1)?));
+                    match self.peek() {
+                        Some(',') => self.index += 1,
+                        Some('}') => {
+                            self.index += 1;
+                            return Ok(JsonValue::Object(members));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some('[') => {
+                self.index += 1;
+                let mut values = Vec::new();
+                if self.peek() == Some(']') {
+                    self.index += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                loop {
+                    values.push(self.parse_value(depth + 1)?);
+                    match self.peek() {
+                        Some(',') => self.index += 1,
+                        Some(']') => {
+                            self.index += 1;
+                            return Ok(JsonValue::Array(values));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('t') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some('f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some('n') => self.parse_literal("null", JsonValue::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
+
+pub fn parse_json(text: &str) -> Result<JsonValue, RoverError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        index: 0,
+    };
+    let value = parser.parse_value(0)?;
+    // Only whitespace may follow the value
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(value)
+}
+
+fn format_json_string(string: &str) -> String {
+    let mut formatted = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => formatted.push_str("\\\""),
+            '\\' => formatted.push_str("\\\\"),
+            '\n' => formatted.push_str("\\n"),
+            '\r' => formatted.push_str("\\r"),
+            '\t' => formatted.push_str("\\t"),
+            c if (c as u32) < 0x20 => formatted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => formatted.push(c),
+        }
+    }
+    formatted.push('"');
+    formatted
+}
+
+pub fn format_json(value: &JsonValue) -> String {
+    // Written compactly on one line, so it also suits line delimited logs
+    match value {
+        JsonValue::Null => "null".to_string(),
+        JsonValue::Bool(b) => b.to_string(),
+        // Whole numbers are written without a fraction, e.g. '5' rather than '5.0'
+        JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => (*n as i64).to_string(),
+        JsonValue::Number(n) if n.is_finite() => n.to_string(),
+        JsonValue::Number(_) => "null".to_string(),
+        JsonValue::String(s) => format_json_string(s),
+        JsonValue::Array(values) => {
+            let values = values.iter().map(format_json).collect::<Vec<_>>();
+            format!("[{}]", values.join(","))
+        }
+        JsonValue::Object(members) => {
+            let members = members
+                .iter()
+                .map(|(name, value)| format!("{}:{}", format_json_string(name), format_json(value)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", members.join(","))
+        }
+    }
+}
@@ -1,4 +1,23 @@
 // Modules mentioned here are ones to be tested/used in the `/tests` directory.
 // This approach is used to enable integration style testing for binary projects.
 
+pub mod check;
+pub mod command;
+pub mod coverage;
+pub mod history;
+pub mod json;
+pub mod live;
+pub mod map;
+pub mod mission;
+pub mod mission_log;
+pub mod objective;
+pub mod optimizer;
+pub mod output;
+pub mod program;
+pub mod repl;
+pub mod replay;
 pub mod rover;
+pub mod server;
+pub mod snapshot;
+pub mod svg;
+pub mod terminal;
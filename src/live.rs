@@ -0,0 +1,168 @@
+use crate::history::History;
+use crate::map::render_map;
+use crate::rover::{
+    simulate_rovers_with_options, InputCommand, MissionOptions, PositionAndHeading, RoverError,
+    Topology,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LiveState {
+    pub program: String, // Every command accepted so far
+    pub trajectory: Vec<PositionAndHeading>,
+}
+
+// A single rover driven one command at a time, the first of its mission. Commands
+// that would stop the rover are rejected, leaving it where it was
+#[derive(Debug, PartialEq, Clone)]
+pub struct LiveRover {
+    pub ur_plateau: (i32, i32),
+    pub options: MissionOptions,
+    pub start: PositionAndHeading,
+    // The mission's other rovers, parked at their start positions in the way of this one
+    pub parked: Vec<PositionAndHeading>,
+    pub history: History<LiveState>, // The rover after each command, so any can be undone
+}
+
+fn trace_program(
+    live_rover: &LiveRover,
+    start: &PositionAndHeading,
+    program: &str,
+) -> Result<Vec<PositionAndHeading>, RoverError> {
+    // Commands are kept apart, so letters pressed in a row are never read as a keyword
+    let program = program
+        .chars()
+        .map(|command| command.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // Parked rovers are deployed first, without moving, so the driven rover is checked
+    // against them like any rover deployed after others. Rover types follow the rovers
+    let mut rovers_to_deploy = live_rover
+        .parked
+        .iter()
+        .map(|parked| (parked.clone(), String::new()))
+        .collect::<Vec<_>>();
+    rovers_to_deploy.push((start.clone(), program));
+    let mut options = live_rover.options.clone();
+    options.rover_profiles = (1..=live_rover.parked.len())
+        .chain(0..1)
+        .map(|rover| {
+            let profiles = &live_rover.options.rover_profiles;
+            profiles.get(rover).cloned().unwrap_or_default()
+        })
+        .collect();
+
+    let input_command = InputCommand {
+        ur_plateau: live_rover.ur_plateau,
+        rovers_to_deploy,
+    };
+    let mut reports = simulate_rovers_with_options(input_command, &options)?;
+    Ok(reports.pop().unwrap().trajectory)
+}
+
+impl LiveRover {
+    pub fn new(
+        ur_plateau: (i32, i32),
+        start: PositionAndHeading,
+        parked: Vec<PositionAndHeading>,
+        options: MissionOptions,
+    ) -> Result<LiveRover, RoverError> {
+        let mut live_rover = LiveRover {
+            ur_plateau,
+            options,
+            start: start.clone(),
+            parked,
+            history: History::new(LiveState {
+                program: String::new(),
+                trajectory: Vec::new(),
+            }),
+        };
+        // An empty program checks the start is somewhere the rover can be
+        let trajectory = trace_program(&live_rover, &start, "")?;
+        live_rover.start = trajectory[0].clone();
+        live_rover.history = History::new(LiveState {
+            program: String::new(),
+            trajectory,
+        });
+        Ok(live_rover)
+    }
+
+    pub fn state(&self) -> &LiveState {
+        self.history.current()
+    }
+
+    pub fn position(&self) -> &PositionAndHeading {
+        let trajectory = &self.state().trajectory;
+        &trajectory[trajectory.len() - 1]
+    }
+
+    pub fn apply(&mut self, command: char) -> Result<&PositionAndHeading, RoverError> {
+        // Anything that isn't a single command, e.g. a count or a bracket, is rejected
+        if !command.is_ascii_alphabetic() {
+            return Err(RoverError::InvalidMove);
+        }
+        // The whole program is run again from the start, so limits that build up
+        // over a run, such as the battery, apply the same as in a mission
+        let program = format!("{}{}", self.state().program, command.to_ascii_uppercase());
+        let trajectory = trace_program(self, &self.start, &program)?;
+        self.history.record(LiveState {
+            program,
+            trajectory,
+        });
+        Ok(self.position())
+    }
+
+    // Both go back or forward up to `count` commands, stopping early when there are
+    // no more, and return how many they moved
+    pub fn undo(&mut self, count: usize) -> usize {
+        (0..count)
+            .take_while(|_| self.history.undo().is_some())
+            .count()
+    }
+
+    pub fn redo(&mut self, count: usize) -> usize {
+        (0..count)
+            .take_while(|_| self.history.redo().is_some())
+            .count()
+    }
+
+    pub fn render(&self) -> String {
+        // Maps are drawn on square cells
+        if self.options.topology == Topology::Hex {
+            return "Plateau maps are only available on square plateaus.\n".to_string();
+        }
+        let mut trajectories = self
+            .parked
+            .iter()
+            .map(|parked| vec![parked.clone()])
+            .collect::<Vec<_>>();
+        trajectories.push(self.state().trajectory.clone());
+        render_map(
+            self.ur_plateau,
+            &self.options.obstacles,
+            &trajectories,
+            true,
+        )
+    }
+
+    // The keys that turn the rover, which depend on the plateau and its rover type
+    pub fn turn_keys(&self) -> String {
+        if self.options.topology == Topology::Hex {
+            return "[L] turn 60 degrees left, [R] turn 60 degrees right".to_string();
+        }
+        let mut keys = "[L] turn left, [R] turn right".to_string();
+        let diagonal = self
+            .options
+            .rover_profiles
+            .first()
+            .is_some_and(|profile| profile.diagonal);
+        if diagonal {
+            let (left, right) = self.options.diagonal_turns;
+            keys.push_str(&format!(
+                ", [{}] turn 45 degrees left, [{}] turn 45 degrees right",
+                left, right
+            ));
+        }
+        keys
+    }
+}
@@ -1,63 +1,675 @@
-use std::io;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub mod rover;
-use rover::{
-    parse_rover_to_deploy, parse_user_plateau, print_final_rover_positions, simulate_rover_move,
+use rusty_rover::check::{check_mission, error_name, print_check_report, simulation_outcomes};
+use rusty_rover::command::CommandRegistry;
+use rusty_rover::coverage::{format_coverage_report, map_coverage, Camera, DEFAULT_CAMERA};
+use rusty_rover::live::LiveRover;
+use rusty_rover::map::{format_map_report, render_map};
+use rusty_rover::mission::{parse_json_mission, parse_mission, Mission};
+use rusty_rover::mission_log::{format_events, mission_events, replay, verify_log};
+use rusty_rover::objective::{format_objectives_report, score_mission, score_objectives};
+use rusty_rover::optimizer::optimize_rover_program;
+use rusty_rover::output::{get_formatter, OutputFormatter, TextFormatter};
+use rusty_rover::program::expand_program;
+use rusty_rover::repl::{Repl, ReplReply, REPL_HELP};
+use rusty_rover::replay::{format_frame, replay_frames, CLEAR_SCREEN, DEFAULT_FRAME_DELAY};
+use rusty_rover::rover::{
+    format_collected_samples, heading_name, simulate_rovers_until_failure, InputCommand,
+    MissionOptions, RoverError, Topology,
 };
+use rusty_rover::server::{serve, DEFAULT_PORT};
+use rusty_rover::snapshot::{
+    format_snapshot, format_snapshot_json, parse_snapshot, parse_snapshot_json, resume_snapshot,
+    take_snapshot,
+};
+use rusty_rover::svg::render_svg;
+use rusty_rover::terminal::{RawMode, INTERRUPT};
 
-fn main() {
+// Flags that are followed by a value, so the value isn't taken as the mission file
+const VALUE_FLAGS: [&str; 10] = [
+    "--camera-range",
+    "--camera-fov",
+    "--format",
+    "--output",
+    "--svg",
+    "--delay",
+    "--snapshot",
+    "--steps",
+    "--log",
+    "--port",
+];
+
+// Modes given as the first argument, before the mission file
+const SUBCOMMANDS: [&str; 6] = ["check", "replay", "live", "resume", "audit", "serve"];
+
+fn subcommand(args: &[String]) -> Option<&str> {
+    args.first()
+        .map(|arg| arg.as_str())
+        .filter(|arg| SUBCOMMANDS.contains(arg))
+}
+
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| arg == flag)
+}
+
+fn flag_text<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    // Flags with values are given as '--flag value'
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+fn flag_value(args: &[String], flag: &str) -> Option<i32> {
+    flag_text(args, flag).and_then(|value| value.parse().ok())
+}
+
+fn mission_format(args: &[String]) -> &str {
+    // Missions are text unless the file is JSON or a format is given
+    match flag_text(args, "--format") {
+        Some(format) => format,
+        None if mission_file(args).is_some_and(|path| path.ends_with(".json")) => "json",
+        None => "text",
+    }
+}
+
+fn mission_file(args: &[String]) -> Option<&String> {
+    // The mission file is the first argument that isn't a subcommand, a flag or a flag's value
+    args.iter().enumerate().find_map(|(index, arg)| {
+        let is_subcommand = index == 0 && subcommand(args).is_some();
+        let is_flag_value = index > 0 && VALUE_FLAGS.contains(&args[index - 1].as_str());
+        if arg.starts_with("--") || is_subcommand || is_flag_value {
+            None
+        } else {
+            Some(arg)
+        }
+    })
+}
+
+fn read_mission_file(path: &str) -> Vec<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => contents
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect(),
+        Err(err) => {
+            println!("Error: {}. Please check the mission file '{}'.", err, path);
+            std::process::exit(1)
+        }
+    }
+}
+
+fn read_piped_lines() -> Vec<String> {
+    // Piped input is read as a whole mission, the same as a mission file. Input
+    // written for the old prompt ends with a 'd' line, which is left out
+    let mut lines = io::stdin()
+        .lines()
+        .map(|line| line.expect("Failed to read line").trim().to_string())
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    if lines.last().is_some_and(|line| line == "d") {
+        lines.pop();
+    }
+    lines
+}
+
+fn run_repl() {
     println!("\nWelcome to NASA's Mars Rover Simulator\n");
-    println!("Commands are entered line by line.");
-    println!("After typing your commands, hit the enter/return key to input the command into the simulator.");
-    println!("To quit, press the 'ctrl+c' keyboard combination.");
+    println!("Enter the plateau and rovers with the commands below, then ':run' to simulate them.");
     println!(
-        "Press 'd' then the return/enter key when you're ready to simulate the commands entered."
+        "Everything entered is kept between runs, so the mission can be changed and run again.\n"
     );
-    println!("Please enter your commands to begin...\n");
-
-    let mut user_input_lines: Vec<String> = Vec::new();
-    let user_input_line_delimiter = "d";
+    print!("{}", REPL_HELP);
 
-    // Start loop to receive user commands of unknown number
+    let mut repl = Repl::new();
     loop {
-        let mut terminal_line = String::new();
+        print!("\n> ");
+        io::stdout().flush().expect("Failed to write prompt");
 
-        io::stdin()
+        // The end of input quits, the same as ':quit'
+        let mut terminal_line = String::new();
+        let bytes_read = io::stdin()
             .read_line(&mut terminal_line)
             .expect("Failed to read line");
-
-        terminal_line = terminal_line.trim().to_string();
-        println!("You entered: '{}'...", terminal_line);
-        // Check if user has finished entering their commands to break loop
-        if terminal_line == user_input_line_delimiter {
+        if bytes_read == 0 {
             break;
         }
 
-        user_input_lines.push(terminal_line);
+        match repl.handle_line(&terminal_line) {
+            ReplReply::Output(output) => print!("{}", output),
+            ReplReply::Quit => break,
+        }
     }
+}
 
-    if !user_input_lines.is_empty() {
-        user_input_lines.reverse(); // Reverse to treat as a stack
+fn print_mission_error(err: &RoverError) {
+    print!("{}", TextFormatter.format_error(err));
+}
+
+fn optimize_rover_programs(
+    input_command: &mut InputCommand,
+    options: &MissionOptions,
+    show_report: bool,
+) {
+    if show_report {
+        println!("\nOptimized rover program(s):\n");
+    }
+    for (index, rover) in input_command.rovers_to_deploy.iter_mut().enumerate() {
+        if let (Ok(expanded_program), Some(optimized_program)) = (
+            expand_program(&rover.1.to_uppercase()),
+            optimize_rover_program(&rover.1, options, index),
+        ) {
+            if show_report {
+                println!(
+                    "Rover {}: {} -> {} instructions ({})",
+                    index + 1,
+                    expanded_program.len(),
+                    optimized_program.len(),
+                    optimized_program
+                );
+            }
+            rover.1 = optimized_program;
+        }
+    }
+}
+
+fn check_expectations(mission: &Mission) {
+    if mission.expectations.is_empty() {
+        println!("Error: no expected outcomes. Please add an 'expect:' section to the mission.");
+        std::process::exit(1)
+    }
+    let checks = match check_mission(mission) {
+        Ok(checks) => checks,
+        Err(err) => {
+            print_mission_error(&err);
+            std::process::exit(1)
+        }
+    };
+    print_check_report(&checks);
+    if checks.iter().any(|check| check.expected != check.actual) {
+        std::process::exit(1)
+    }
+}
+
+fn replay_mission(mission: Mission, delay: Duration) {
+    // Frames are drawn on square cells, like maps
+    if mission.options.topology == Topology::Hex {
+        println!("Error: replays are only available on square plateaus.");
+        std::process::exit(1)
+    }
+    let ur_plateau = mission.input_command.ur_plateau;
+    let result = match simulate_rovers_until_failure(mission.input_command, &mission.options) {
+        Ok(result) => result,
+        Err(err) => {
+            print_mission_error(&err);
+            std::process::exit(1)
+        }
+    };
+    let frames = replay_frames(&result);
+
+    // Keys are only read when the terminal allows it, otherwise the replay just plays
+    let raw_mode = RawMode::enable(false);
+    let mut paused = false;
+    let mut index = 0;
+    while index < frames.len() {
+        print!(
+            "{}{}",
+            CLEAR_SCREEN,
+            format_frame(ur_plateau, &mission.options.obstacles, &frames[index])
+        );
+        if raw_mode.is_enabled() {
+            let state = if paused { " (paused)" } else { "" };
+            println!("\n[space] pause/resume, [n] next step, [q] quit{}", state);
+        }
+        io::stdout().flush().expect("Failed to write frame");
+
+        // Wait out the frame delay while watching for keys
+        let shown_at = Instant::now();
+        loop {
+            match raw_mode.read_key() {
+                Some(' ') => {
+                    paused = !paused;
+                    break;
+                }
+                Some('n') if paused => {
+                    index += 1;
+                    break;
+                }
+                Some('q') | Some(INTERRUPT) => return,
+                _ => {}
+            }
+            if !paused && shown_at.elapsed() >= delay {
+                index += 1;
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+fn drive_live_rover(mission: Mission) {
+    // Only the first rover is driven, from its start position, on the mission's plateau.
+    // The other rovers are parked at their start positions
+    let mut starts = mission
+        .input_command
+        .rovers_to_deploy
+        .iter()
+        .map(|(start, _)| start.clone());
+    let start = starts.next().expect("Missions have at least one rover");
+    let parked = starts.collect();
+    let ur_plateau = mission.input_command.ur_plateau;
+    let mut rover = match LiveRover::new(ur_plateau, start, parked, mission.options) {
+        Ok(rover) => rover,
+        Err(err) => {
+            print_mission_error(&err);
+            std::process::exit(1)
+        }
+    };
+
+    // Keys are read as they're pressed in a terminal, or one character at a time when piped in
+    let raw_mode = RawMode::enable(true);
+    let mut message = String::new();
+    // Digits typed before '-' or '+' say how many commands to undo or redo
+    let mut count = String::new();
+    loop {
+        let position = rover.position();
+        print!(
+            "{}Rover at {} {} {}\n\n{}\n{}\n",
+            CLEAR_SCREEN,
+            position.x,
+            position.y,
+            heading_name(position.heading),
+            rover.render(),
+            message
+        );
+        println!(
+            "{}, [M] move, [N-] undo, [N+] redo, [q] quit",
+            rover.turn_keys()
+        );
+        io::stdout().flush().expect("Failed to write map");
+
+        let key = if raw_mode.is_enabled() {
+            raw_mode.read_key()
+        } else {
+            io::stdin()
+                .lock()
+                .bytes()
+                .next()
+                .and_then(|byte| byte.ok())
+                .map(|byte| byte as char)
+        };
+        if let Some(digit) = key.filter(|key| key.is_ascii_digit()) {
+            count.push(digit);
+            message = format!("Count {}", count);
+            continue;
+        }
+        let undo_count = count.parse().unwrap_or(1);
+        count.clear();
+        message = match key {
+            None | Some('q') | Some(INTERRUPT) => break,
+            Some(key) if key.is_whitespace() => continue,
+            // Letters can all be commands, so undo and redo use symbols
+            Some('-') => match rover.undo(undo_count) {
+                0 => "Nothing to undo".to_string(),
+                undone => format!("Undid {} command(s)", undone),
+            },
+            Some('+') => match rover.redo(undo_count) {
+                0 => "Nothing to redo".to_string(),
+                redone => format!("Redid {} command(s)", redone),
+            },
+            Some(key) => match rover.apply(key) {
+                Ok(_) => format!("Applied {}", key.to_ascii_uppercase()),
+                Err(err) => format!(
+                    "Rejected {}: {}",
+                    key.to_ascii_uppercase(),
+                    error_name(&err)
+                ),
+            },
+        };
+    }
+
+    let position = rover.position();
+    println!(
+        "\nFinal rover position: {} {} {}",
+        position.x,
+        position.y,
+        heading_name(position.heading)
+    );
+    println!("Program driven: {}", rover.state().program);
+}
+
+fn save_snapshot(path: &str, mission: &Mission, steps: usize, show_report: bool) {
+    let snapshot = match take_snapshot(mission.input_command.clone(), &mission.options, steps) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            print_mission_error(&err);
+            std::process::exit(1)
+        }
+    };
+    // Snapshots are JSON when the file is, and text otherwise
+    let contents = if path.ends_with(".json") {
+        format_snapshot_json(&snapshot)
+    } else {
+        format_snapshot(&snapshot)
+    };
+    if let Err(err) = fs::write(path, contents) {
+        println!("Error: {}. Please check the snapshot file '{}'.", err, path);
+        std::process::exit(1)
+    }
+    if show_report {
+        println!("\nSnapshot written to '{}'", path);
+    }
+}
+
+fn resume_from_snapshot(text: &str, format: &str, formatter: &dyn OutputFormatter) {
+    let parsed_snapshot = if format == "json" {
+        parse_snapshot_json(text)
+    } else {
+        parse_snapshot(text)
+    };
+    let resumed = parsed_snapshot.and_then(|snapshot| {
+        let result = resume_snapshot(&snapshot, &CommandRegistry::new())?;
+        Ok(simulation_outcomes(&result, snapshot.rovers.len()))
+    });
+    match resumed {
+        Ok(outcomes) => print!("{}", formatter.format_results(&outcomes)),
+        Err(err) => {
+            print!("{}", formatter.format_error(&err));
+            std::process::exit(1)
+        }
+    }
+}
+
+fn append_log(path: &str, mission: &Mission) {
+    // Invalid missions never start, so they have nothing to log
+    let result =
+        match simulate_rovers_until_failure(mission.input_command.clone(), &mission.options) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+    let appended = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(format_events(&mission_events(&result)).as_bytes()));
+    if let Err(err) = appended {
+        println!("Error: {}. Please check the log file '{}'.", err, path);
+        std::process::exit(1)
+    }
+}
+
+fn audit_log(args: &[String], mission: &Mission) {
+    let path = match flag_text(args, "--log") {
+        Some(path) => path,
+        None => {
+            println!("Error: no log. Please give the log file with '--log FILE'.");
+            std::process::exit(1)
+        }
+    };
+    let log = match fs::read_to_string(path) {
+        Ok(log) => log,
+        Err(err) => {
+            println!("Error: {}. Please check the log file '{}'.", err, path);
+            std::process::exit(1)
+        }
+    };
+    match replay(&log, &mission.options)
+        .and_then(|outcomes| Ok((outcomes, verify_log(&log, mission)?)))
+    {
+        Ok((outcomes, matches)) => {
+            println!("\nLogged outcome(s):\n");
+            print!("{}", TextFormatter.format(&outcomes));
+            if matches {
+                println!("\nThe log matches a fresh run of the mission.");
+            } else {
+                println!("\nThe log doesn't match a fresh run of the mission.");
+                std::process::exit(1)
+            }
+        }
+        Err(err) => {
+            println!("Error: {:?}. Please check the log file '{}'.", err, path);
+            std::process::exit(1)
+        }
+    }
+}
+
+fn serve_api(args: &[String]) {
+    let port = match flag_text(args, "--port") {
+        Some(port) => match port.parse() {
+            Ok(port) => port,
+            Err(_) => {
+                println!(
+                    "Error: invalid port '{}'. Please give a number up to 65535.",
+                    port
+                );
+                std::process::exit(1)
+            }
+        },
+        None => DEFAULT_PORT,
+    };
+    if let Err(err) = serve(port) {
+        println!("Error: {}. Please check port {} is free.", err, port);
+        std::process::exit(1)
+    }
+}
+
+fn write_svg(path: &str, svg: &str) {
+    if let Err(err) = fs::write(path, svg) {
+        println!("Error: {}. Please check the SVG file '{}'.", err, path);
+        std::process::exit(1)
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let show_coverage = has_flag(&args, "--coverage");
+    let optimize = has_flag(&args, "--optimize");
+    // Drawing the paths implies drawing the map
+    let show_path = has_flag(&args, "--path");
+    let show_map = has_flag(&args, "--map") || show_path;
+    let camera = Camera {
+        range: flag_value(&args, "--camera-range").unwrap_or(DEFAULT_CAMERA.range),
+        field_of_view: flag_value(&args, "--camera-fov").unwrap_or(DEFAULT_CAMERA.field_of_view),
+    };
+
+    let format = mission_format(&args);
+    if format != "text" && format != "json" {
+        println!(
+            "Error: unknown format '{}'. Please use 'text' or 'json'.",
+            format
+        );
+        std::process::exit(1)
+    }
+
+    // Anything other than text output is only the rover results, for other programs to read
+    let output = flag_text(&args, "--output").unwrap_or("text");
+    let formatter = match get_formatter(output) {
+        Some(formatter) => formatter,
+        None => {
+            println!(
+                "Error: unknown output '{}'. Please use 'text', 'json' or 'csv'.",
+                output
+            );
+            std::process::exit(1)
+        }
+    };
+
+    // The server takes its missions from requests, not a file
+    if subcommand(&args) == Some("serve") {
+        serve_api(&args);
+        return;
+    }
 
-        let ur_plateau = match parse_user_plateau(user_input_lines.pop().unwrap()) {
-            Ok(plateau) => plateau,
+    let user_input_lines = match mission_file(&args) {
+        Some(path) => read_mission_file(path),
+        None if io::stdin().is_terminal() => {
+            run_repl();
+            return;
+        }
+        None => read_piped_lines(),
+    };
+
+    if subcommand(&args) == Some("resume") {
+        resume_from_snapshot(&user_input_lines.join("\n"), format, formatter.as_ref());
+        return;
+    }
+
+    if !user_input_lines.is_empty() {
+        let parsed_mission = if format == "json" {
+            parse_json_mission(&user_input_lines.join("\n"))
+        } else {
+            parse_mission(user_input_lines)
+        };
+        let mut mission = match parsed_mission {
+            Ok(mission) => mission,
             Err(err) => {
-                println!("Error: {:?}. Please check plateau coordinates.", err);
+                print!("{}", formatter.format_error(&err));
                 std::process::exit(1)
             }
         };
 
-        let input_command = match parse_rover_to_deploy(ur_plateau, user_input_lines) {
-            Ok(input_command) => input_command,
+        if subcommand(&args) == Some("check") {
+            check_expectations(&mission);
+            return;
+        }
+
+        if subcommand(&args) == Some("audit") {
+            audit_log(&args, &mission);
+            return;
+        }
+
+        if optimize {
+            optimize_rover_programs(
+                &mut mission.input_command,
+                &mission.options,
+                output == "text",
+            );
+        }
+
+        if let Some(path) = flag_text(&args, "--snapshot") {
+            // Without a number of steps, the snapshot is of the end of the mission
+            let steps = flag_text(&args, "--steps")
+                .and_then(|steps| steps.parse().ok())
+                .unwrap_or(usize::MAX);
+            save_snapshot(path, &mission, steps, output == "text");
+        }
+
+        if let Some(path) = flag_text(&args, "--log") {
+            append_log(path, &mission);
+        }
+
+        if subcommand(&args) == Some("live") {
+            drive_live_rover(mission);
+            return;
+        }
+
+        if subcommand(&args) == Some("replay") {
+            let delay = flag_text(&args, "--delay")
+                .and_then(|delay| delay.parse().ok())
+                .unwrap_or(DEFAULT_FRAME_DELAY);
+            replay_mission(mission, Duration::from_millis(delay));
+            return;
+        }
+
+        // With json or csv output only the rover results are written to stdout, for
+        // other programs to read, so the other reports are written to stderr
+        let print_report = |report: String| {
+            if output == "text" {
+                print!("{}", report);
+            } else {
+                eprint!("{}", report);
+            }
+        };
+
+        let ur_plateau = mission.input_command.ur_plateau;
+        let rover_count = mission.input_command.rovers_to_deploy.len();
+        let result = match simulate_rovers_until_failure(mission.input_command, &mission.options) {
+            Ok(result) => result,
             Err(err) => {
-                println!("Error: {:?}. Please check your rover command(s).", err);
-                std::process::exit(1)
+                print!("{}", formatter.format_error(&err));
+                // No rover finished, so every objective is missed
+                if !mission.objectives.is_empty() {
+                    print_report(format_objectives_report(&score_objectives(
+                        &mission.objectives,
+                        &[],
+                    )));
+                    std::process::exit(1)
+                }
+                return;
             }
         };
 
-        match simulate_rover_move(input_command) {
-            Ok(rover_positions) => print_final_rover_positions(rover_positions),
-            Err(err) => println!("Error: {:?}. Please check your rover command(s).", err),
+        print!(
+            "{}",
+            formatter.format_results(&simulation_outcomes(&result, rover_count))
+        );
+
+        // The SVG is written even when a rover fails, to show where it was stopped.
+        // Like maps, it's drawn on square cells
+        let svg_path = flag_text(&args, "--svg");
+        if svg_path.is_some() && mission.options.topology == Topology::Hex {
+            print_report("\nSVG images are only available on square plateaus.\n".to_string());
+        } else if let Some(path) = svg_path {
+            write_svg(
+                path,
+                &render_svg(ur_plateau, &mission.options.obstacles, &result),
+            );
+        }
+
+        // Only rovers that finished their programs are reported on
+        let reports = &result.reports;
+        if !mission.options.sample_sites.is_empty() {
+            print_report(format_collected_samples(reports));
+        }
+
+        // Maps are drawn on square cells, like coverage maps
+        if show_map && mission.options.topology == Topology::Hex {
+            print_report("\nPlateau maps are only available on square plateaus.\n".to_string());
+        } else if show_map {
+            let trajectories = reports
+                .iter()
+                .map(|report| report.trajectory.clone())
+                .collect::<Vec<_>>();
+            print_report(format_map_report(&render_map(
+                ur_plateau,
+                &mission.options.obstacles,
+                &trajectories,
+                show_path,
+            )));
+        }
+
+        // Every rover carries the same camera when run from the terminal. Cameras
+        // are modelled on square cells, so hex plateaus have no coverage map
+        if show_coverage && mission.options.topology == Topology::Hex {
+            print_report("\nCoverage maps are only available on square plateaus.\n".to_string());
+        } else if show_coverage {
+            // A rover stopped with an error still photographed the cells on its way
+            let trajectories = reports
+                .iter()
+                .chain(result.failure.iter().map(|failure| &failure.report))
+                .map(|report| report.trajectory.clone())
+                .collect::<Vec<_>>();
+            let cameras = vec![camera; trajectories.len()];
+            print_report(format_coverage_report(&map_coverage(
+                ur_plateau,
+                &trajectories,
+                &cameras,
+            )));
+        }
+
+        // Fail the run when any objective is missed, so scripts can tell
+        if !mission.objectives.is_empty() {
+            let score_report = score_mission(&mission.objectives, &result);
+            print_report(format_objectives_report(&score_report));
+            if !score_report.passed {
+                std::process::exit(1)
+            }
         }
     }
 }
@@ -0,0 +1,74 @@
+use crate::rover::{PositionAndHeading, NORTH_EAST, NORTH_WEST, SOUTH_EAST, SOUTH_WEST};
+
+pub fn heading_glyph(heading: char) -> char {
+    // Diagonal headings share a glyph with their opposite, as ASCII has no diagonal arrows
+    match heading {
+        'N' => '^',
+        'E' => '>',
+        'S' => 'v',
+        'W' => '<',
+        NORTH_EAST | SOUTH_WEST => '/',
+        NORTH_WEST | SOUTH_EAST => '\\',
+        _ => '?',
+    }
+}
+
+pub fn render_map(
+    ur_plateau: (i32, i32),
+    obstacles: &[(i32, i32)],
+    trajectories: &[Vec<PositionAndHeading>],
+    show_path: bool,
+) -> String {
+    // Cells indexed as cells[y][x], with y = 0 being the southern edge
+    let mut cells =
+        vec![vec!['.'; (ur_plateau.0 + 1).max(0) as usize]; (ur_plateau.1 + 1).max(0) as usize];
+    // Anything off the plateau is left out
+    let mut draw = |(x, y): (i32, i32), glyph: char| {
+        if x < 0 || y < 0 {
+            return;
+        }
+        if let Some(cell) = cells
+            .get_mut(y as usize)
+            .and_then(|row| row.get_mut(x as usize))
+        {
+            *cell = glyph;
+        }
+    };
+
+    for obstacle in obstacles {
+        draw(*obstacle, '#');
+    }
+    // Rovers are drawn over every path, so a later rover's path never hides an earlier rover
+    if show_path {
+        for trajectory in trajectories {
+            for position in trajectory {
+                draw((position.x, position.y), '*');
+            }
+        }
+    }
+    for position in trajectories
+        .iter()
+        .filter_map(|trajectory| trajectory.last())
+    {
+        draw((position.x, position.y), heading_glyph(position.heading));
+    }
+
+    // Draw north at the top, so rows are printed from the highest y down
+    let mut output = String::new();
+    for row in cells.iter().rev() {
+        output.push_str(&row.iter().collect::<String>());
+        output.push('\n');
+    }
+    output
+}
+
+pub fn format_map_report(map: &str) -> String {
+    format!(
+        "\nPlateau map ('^', '>', 'v', '<' rovers, '/', '\\' diagonal rovers, '#' obstacles, '*' path):\n\n{}",
+        map
+    )
+}
+
+pub fn print_map(map: &str) {
+    print!("{}", format_map_report(map));
+}
@@ -0,0 +1,829 @@
+use crate::check::{parse_outcome, Outcome};
+use crate::command::BUILT_IN_COMMANDS;
+use crate::json::{parse_json, JsonValue};
+use crate::objective::Objective;
+use crate::program::{parse_program, MAX_NESTING_DEPTH, MAX_PROGRAM_LENGTH};
+use crate::rover::{
+    parse_heading, parse_rover_to_deploy, parse_user_plateau, simulate_rover_move_with_options,
+    InputCommand, MissionOptions, PositionAndHeading, RoverError, RoverProfile, Topology,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mission {
+    pub input_command: InputCommand,
+    pub options: MissionOptions,
+    pub objectives: Vec<Objective>,
+    pub expectations: Vec<Outcome>, // What each rover is expected to end up doing
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Macro {
+    pub name: String,
+    pub body: String,
+    pub line: usize, // Line of the mission file the macro was defined on, starting at 1
+}
+
+fn is_macro_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_macro(definition: &str, line: usize) -> Result<Macro, RoverError> {
+    // Definitions look like 'def SQUARE = (MMR)4'
+    let (name, body) = match definition.find('=') {
+        Some(index) => (&definition[..index], Some(&definition[index + 1..])),
+        None => (definition.split_whitespace().next().unwrap_or(""), None),
+    };
+    let name = name.trim().to_uppercase();
+
+    match body {
+        Some(body) if is_macro_name(&name) => Ok(Macro {
+            name,
+            body: body.trim().to_string(),
+            line,
+        }),
+        _ => Err(RoverError::InvalidMacro { name, line }),
+    }
+}
+
+fn expand_macros(
+    program: &str,
+    macros: &[Macro],
+    expanding: &mut Vec<String>,
+) -> Result<String, RoverError> {
+    let mut expanded = String::new();
+    let mut chars = program.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '@' {
+            expanded.push(c);
+            continue;
+        }
+        // Macros are invoked by name, e.g. '@SQUARE', and names aren't case sensitive
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next.is_ascii_alphanumeric() || next == '_' {
+                name.push(next.to_ascii_uppercase());
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        let called_macro = match macros.iter().find(|m| m.name == name) {
+            Some(called_macro) => called_macro,
+            None => return Err(RoverError::UndefinedMacro(name)),
+        };
+        // Every call is wrapped in a group, so calls nested too deep could never be parsed
+        if expanding.len() > MAX_NESTING_DEPTH {
+            return Err(RoverError::ProgramTooLong);
+        }
+        if expanding.contains(&called_macro.name) {
+            return Err(RoverError::RecursiveMacro {
+                name: called_macro.name.clone(),
+                line: called_macro.line,
+            });
+        }
+
+        expanding.push(called_macro.name.clone());
+        let body = expand_macros(&called_macro.body, macros, expanding)?;
+        expanding.pop();
+
+        // Wrap the body in a group so a count before the call repeats all of it. Only
+        // a count straight after a bracket repeats a group, and names take every digit
+        // after the '@', so a count after the call always counts what follows it
+        expanded.push('(');
+        expanded.push_str(&body);
+        expanded.push(')');
+        if expanded.len() > MAX_PROGRAM_LENGTH {
+            return Err(RoverError::ProgramTooLong);
+        }
+    }
+    Ok(expanded)
+}
+
+fn validate_macro(checked_macro: &Macro, macros: &[Macro]) -> Result<(), RoverError> {
+    let invalid_macro = || RoverError::InvalidMacro {
+        name: checked_macro.name.clone(),
+        line: checked_macro.line,
+    };
+
+    // Report problems against the definition rather than the rover that uses it
+    let body = match expand_macros(
+        &checked_macro.body,
+        macros,
+        &mut vec![checked_macro.name.clone()],
+    ) {
+        Ok(body) => body,
+        Err(RoverError::UndefinedMacro(_)) => return Err(invalid_macro()),
+        Err(err) => return Err(err),
+    };
+    match parse_program(&body) {
+        Err(RoverError::InvalidMove) => Err(invalid_macro()),
+        Err(err) => Err(err),
+        Ok(_) => Ok(()),
+    }
+}
+
+pub fn parse_macros(lines: &[String]) -> Result<Vec<Macro>, RoverError> {
+    let mut macros: Vec<Macro> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(definition) = line.trim().strip_prefix("def ") {
+            let new_macro = parse_macro(definition, index + 1)?;
+            if macros.iter().any(|m| m.name == new_macro.name) {
+                return Err(RoverError::InvalidMacro {
+                    name: new_macro.name,
+                    line: new_macro.line,
+                });
+            }
+            macros.push(new_macro);
+        }
+    }
+    // Macros may call ones defined further down, so only check them once all are known
+    for defined_macro in &macros {
+        validate_macro(defined_macro, &macros)?;
+    }
+    Ok(macros)
+}
+
+// Keywords starting lines that set up the mission rather than the plateau or a rover
+const DIRECTIVES: [&str; 10] = [
+    "def",
+    "obstacle",
+    "sample",
+    "diagonal",
+    "diagonal-turns",
+    "topology",
+    "collisions",
+    "type",
+    "rover",
+    "objective",
+];
+
+pub fn is_directive(line: &str) -> bool {
+    match line.split_whitespace().next() {
+        Some(keyword) => DIRECTIVES.contains(&keyword),
+        None => false,
+    }
+}
+
+fn on_plateau(cell: (i32, i32), ur_plateau: (i32, i32)) -> Option<(i32, i32)> {
+    let (x, y) = cell;
+    if (0..=ur_plateau.0).contains(&x) && (0..=ur_plateau.1).contains(&y) {
+        Some(cell)
+    } else {
+        None
+    }
+}
+
+fn parse_cell(cell: &[&str], ur_plateau: (i32, i32)) -> Option<(i32, i32)> {
+    // Cells are given as 'X Y' after the directive, e.g. 'obstacle 1 2', and must
+    // be on the plateau
+    match cell {
+        [x, y] => on_plateau((x.parse().ok()?, y.parse().ok()?), ur_plateau),
+        _ => None,
+    }
+}
+
+fn parse_diagonal_rovers(rovers: &[&str], options: &mut MissionOptions) -> Option<()> {
+    // Rovers are numbered from 1 in the order they're deployed, e.g. 'diagonal 1 3'
+    for rover in rovers {
+        let index = rover.parse::<usize>().ok()?.checked_sub(1)?;
+        if options.rover_profiles.len() <= index {
+            options
+                .rover_profiles
+                .resize(index + 1, RoverProfile::default());
+        }
+        options.rover_profiles[index].diagonal = true;
+    }
+    if rovers.is_empty() {
+        None
+    } else {
+        Some(())
+    }
+}
+
+fn parse_diagonal_turns(turns: &[&str]) -> Option<(char, char)> {
+    // The left and right turn commands, e.g. 'diagonal-turns A C', which can't
+    // reuse the commands every rover already has
+    match turns {
+        [left, right] if left.len() == 1 && right.len() == 1 => {
+            let left = left.chars().next()?.to_ascii_uppercase();
+            let right = right.chars().next()?.to_ascii_uppercase();
+            let is_new_command =
+                |c: char| c.is_ascii_alphabetic() && !BUILT_IN_COMMANDS.contains(&c);
+            if left != right && is_new_command(left) && is_new_command(right) {
+                Some((left, right))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_capability(profile: &mut RoverProfile, capability: &str) -> Option<()> {
+    // Capabilities are given as 'key=value', e.g. 'speed=2' or 'commands=LRM'
+    let (key, value) = capability.split_at(capability.find('=')?);
+    let value = &value[1..];
+    match key {
+        "speed" => profile.speed = value.parse().ok().filter(|speed| *speed > 0)?,
+        "commands" if value.chars().all(|c| c.is_ascii_alphabetic()) => {
+            profile.allowed_commands = Some(value.to_ascii_uppercase().chars().collect())
+        }
+        "turning" => {
+            profile.diagonal = match value {
+                "45" => true,
+                "90" => false,
+                _ => return None,
+            }
+        }
+        "battery" => profile.battery = Some(value.parse().ok()?),
+        "payload" => profile.payload = Some(value.parse().ok()?),
+        "footprint" => {
+            // Footprints are given across by front to back, e.g. 'footprint=2x3'
+            let (across, length) = value.split_at(value.find('x')?);
+            profile.footprint = (across.parse().ok()?, length[1..].parse().ok()?);
+            if profile.footprint.0 == 0 || profile.footprint.1 == 0 {
+                return None;
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+pub fn format_capabilities(profile: &RoverProfile) -> Vec<String> {
+    // Only capabilities that differ from the default are written, e.g. ['speed=2']
+    let mut capabilities = Vec::new();
+    if profile.speed != 1 {
+        capabilities.push(format!("speed={}", profile.speed));
+    }
+    if let Some(commands) = &profile.allowed_commands {
+        capabilities.push(format!("commands={}", commands.iter().collect::<String>()));
+    }
+    if profile.diagonal {
+        capabilities.push("turning=45".to_string());
+    }
+    if let Some(battery) = profile.battery {
+        capabilities.push(format!("battery={}", battery));
+    }
+    if let Some(payload) = profile.payload {
+        capabilities.push(format!("payload={}", payload));
+    }
+    if profile.footprint != (1, 1) {
+        capabilities.push(format!(
+            "footprint={}x{}",
+            profile.footprint.0, profile.footprint.1
+        ));
+    }
+    capabilities
+}
+
+fn parse_rover_types(lines: &[String]) -> Result<Vec<(String, RoverProfile)>, RoverError> {
+    // Types are given as 'type NAME key=value ...' and can be used before they're declared
+    let mut rover_types: Vec<(String, RoverProfile)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if let Some((&"type", definition)) = words.split_first() {
+            let invalid_option = || RoverError::InvalidOption { line: index + 1 };
+            let (name, capabilities) = definition.split_first().ok_or_else(invalid_option)?;
+            let name = name.to_uppercase();
+            if !is_macro_name(&name) || rover_types.iter().any(|(n, _)| *n == name) {
+                return Err(invalid_option());
+            }
+
+            let mut profile = RoverProfile::default();
+            for capability in capabilities {
+                parse_capability(&mut profile, capability).ok_or_else(invalid_option)?;
+            }
+            // Larger rovers only turn at right angles, so their footprint stays on the grid
+            if profile.diagonal && profile.footprint != (1, 1) {
+                return Err(invalid_option());
+            }
+            rover_types.push((name, profile));
+        }
+    }
+    Ok(rover_types)
+}
+
+fn parse_rover_type_assignment(
+    assignment: &[&str],
+    rover_types: &[(String, RoverProfile)],
+    options: &mut MissionOptions,
+) -> Option<()> {
+    // Rovers are given a type by their number counting from 1, e.g. 'rover 2 HEAVY'
+    match assignment {
+        [rover, name] => {
+            let index = rover.parse::<usize>().ok()?.checked_sub(1)?;
+            let name = name.to_uppercase();
+            let (_, profile) = rover_types.iter().find(|(n, _)| *n == name)?;
+            if options.rover_profiles.len() <= index {
+                options
+                    .rover_profiles
+                    .resize(index + 1, RoverProfile::default());
+            }
+            // Rovers listed as diagonal stay diagonal whatever their type
+            let diagonal = options.rover_profiles[index].diagonal || profile.diagonal;
+            options.rover_profiles[index] = RoverProfile {
+                diagonal,
+                ..profile.clone()
+            };
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_options(
+    lines: &[String],
+    ur_plateau: (i32, i32),
+) -> Result<MissionOptions, RoverError> {
+    let mut options = MissionOptions::default();
+    let rover_types = parse_rover_types(lines)?;
+
+    for (index, line) in lines.iter().enumerate() {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let line = index + 1;
+        match words.split_first() {
+            Some((&"obstacle", cell)) => match parse_cell(cell, ur_plateau) {
+                Some(obstacle) => options.obstacles.push(obstacle),
+                None => return Err(RoverError::InvalidObstacle { line }),
+            },
+            Some((&"sample", cell)) => match parse_cell(cell, ur_plateau) {
+                Some(sample_site) => options.sample_sites.push(sample_site),
+                None => return Err(RoverError::InvalidOption { line }),
+            },
+            Some((&"diagonal", rovers)) => {
+                parse_diagonal_rovers(rovers, &mut options)
+                    .ok_or(RoverError::InvalidOption { line })?;
+            }
+            Some((&"diagonal-turns", turns)) => {
+                options.diagonal_turns =
+                    parse_diagonal_turns(turns).ok_or(RoverError::InvalidOption { line })?;
+            }
+            Some((&"rover", assignment)) => {
+                parse_rover_type_assignment(assignment, &rover_types, &mut options)
+                    .ok_or(RoverError::InvalidOption { line })?;
+            }
+            Some((&"topology", topology)) => {
+                options.topology = match topology {
+                    ["square"] => Topology::Square,
+                    ["hex"] => Topology::Hex,
+                    _ => return Err(RoverError::InvalidOption { line }),
+                };
+            }
+            Some((&"collisions", collisions)) => {
+                options.path_collisions = match collisions {
+                    ["final"] => false,
+                    ["path"] => true,
+                    _ => return Err(RoverError::InvalidOption { line }),
+                };
+            }
+            _ => {}
+        }
+    }
+    Ok(options)
+}
+
+fn parse_objective(
+    objective: &[&str],
+    ur_plateau: (i32, i32),
+    rover_count: usize,
+) -> Option<Objective> {
+    // Objectives are given for a rover by its number counting from 1, e.g.
+    // 'objective 1 reach 3 4' or 'objective 2 waypoints 1 1 2 2'
+    let (rover, objective) = objective.split_first()?;
+    let rover = rover.parse::<usize>().ok()?.checked_sub(1)?;
+    if rover >= rover_count {
+        return None;
+    }
+    match objective {
+        ["reach", cell @ ..] => Some(Objective::Reach {
+            rover,
+            cell: parse_cell(cell, ur_plateau)?,
+        }),
+        ["heading", heading] if heading.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some(Objective::Heading {
+                rover,
+                heading: parse_heading(heading)?,
+            })
+        }
+        ["waypoints", cells @ ..] if !cells.is_empty() && cells.len() % 2 == 0 => {
+            let cells = cells
+                .chunks(2)
+                .map(|cell| parse_cell(cell, ur_plateau))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Objective::Waypoints { rover, cells })
+        }
+        ["samples", count] => Some(Objective::Samples {
+            rover,
+            count: count.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_objectives(
+    lines: &[String],
+    ur_plateau: (i32, i32),
+    rover_count: usize,
+) -> Result<Vec<Objective>, RoverError> {
+    let mut objectives = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if let Some((&"objective", objective)) = words.split_first() {
+            match parse_objective(objective, ur_plateau, rover_count) {
+                Some(objective) => objectives.push(objective),
+                None => return Err(RoverError::InvalidOption { line: index + 1 }),
+            }
+        }
+    }
+    Ok(objectives)
+}
+
+fn parse_expectations(lines: &[String], first_line: usize) -> Result<Vec<Outcome>, RoverError> {
+    // One outcome per rover in the order they're deployed, skipping blank lines
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_outcome(line).ok_or(RoverError::InvalidOption {
+                line: first_line + index,
+            })
+        })
+        .collect()
+}
+
+pub fn parse_mission(lines: Vec<String>) -> Result<Mission, RoverError> {
+    // Everything after an 'expect:' line is the expected outcome of each rover
+    let mut lines = lines;
+    let expectations = match lines.iter().position(|line| line.trim() == "expect:") {
+        Some(index) => {
+            let expectations = parse_expectations(&lines[index + 1..], index + 2)?;
+            lines.truncate(index);
+            expectations
+        }
+        None => Vec::new(),
+    };
+
+    let macros = parse_macros(&lines)?;
+    let mut mission_lines = lines
+        .iter()
+        .filter(|line| !is_directive(line))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if mission_lines.is_empty() {
+        return Err(RoverError::InvalidPlateau);
+    }
+    let ur_plateau = parse_user_plateau(mission_lines.remove(0))?;
+    let options = parse_options(&lines, ur_plateau)?;
+
+    // Rovers are given as a start position line followed by a program line
+    for program in mission_lines.iter_mut().skip(1).step_by(2) {
+        *program = expand_macros(program, &macros, &mut Vec::new())?;
+    }
+    mission_lines.reverse(); // Reverse to treat as a stack
+
+    let input_command = parse_rover_to_deploy(ur_plateau, mission_lines)?;
+    let objectives = parse_objectives(&lines, ur_plateau, input_command.rovers_to_deploy.len())?;
+    if expectations.len() > input_command.rovers_to_deploy.len() {
+        return Err(RoverError::InvalidOption {
+            line: lines.len() + 1,
+        });
+    }
+    Ok(Mission {
+        input_command,
+        options,
+        objectives,
+        expectations,
+    })
+}
+
+pub fn invalid_json(path: &str, problem: &str) -> RoverError {
+    RoverError::InvalidJson(format!("'{}' {}", path, problem))
+}
+
+pub fn json_cells(
+    cells: Option<&JsonValue>,
+    ur_plateau: (i32, i32),
+    path: &str,
+) -> Result<Vec<(i32, i32)>, RoverError> {
+    // Cells are given as '[x, y]' pairs, e.g. '"obstacles": [[1, 2], [3, 3]]'
+    let cells = match cells {
+        Some(cells) => cells
+            .as_array()
+            .ok_or_else(|| invalid_json(path, "must be a list of cells"))?,
+        None => return Ok(Vec::new()),
+    };
+    cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| {
+            let cell = match cell.as_array() {
+                Some([x, y]) => x.as_i32().zip(y.as_i32()),
+                _ => None,
+            };
+            cell.and_then(|cell| on_plateau(cell, ur_plateau))
+                .ok_or_else(|| {
+                    invalid_json(
+                        &format!("{}[{}]", path, index),
+                        "must be a cell on the plateau",
+                    )
+                })
+        })
+        .collect()
+}
+
+pub fn json_rover_type(rover_type: &JsonValue, path: &str) -> Result<RoverProfile, RoverError> {
+    // Types take the same capabilities as in mission files, e.g. '{"speed": 2}'
+    let capabilities = match rover_type {
+        JsonValue::Object(capabilities) => capabilities,
+        _ => return Err(invalid_json(path, "must be an object")),
+    };
+    let mut profile = RoverProfile::default();
+
+    for (key, value) in capabilities {
+        let invalid_capability = || invalid_json(&format!("{}.{}", path, key), "is invalid");
+        match key.as_str() {
+            "speed" => {
+                profile.speed = value
+                    .as_usize()
+                    .filter(|speed| *speed > 0)
+                    .ok_or_else(invalid_capability)?
+            }
+            "commands" => {
+                let commands = value
+                    .as_str()
+                    .filter(|commands| commands.chars().all(|c| c.is_ascii_alphabetic()))
+                    .ok_or_else(invalid_capability)?;
+                profile.allowed_commands = Some(commands.to_ascii_uppercase().chars().collect());
+            }
+            "turning" => {
+                profile.diagonal = match value.as_i32() {
+                    Some(45) => true,
+                    Some(90) => false,
+                    _ => return Err(invalid_capability()),
+                }
+            }
+            "battery" => profile.battery = Some(value.as_usize().ok_or_else(invalid_capability)?),
+            "payload" => profile.payload = Some(value.as_usize().ok_or_else(invalid_capability)?),
+            "footprint" => {
+                profile.footprint = match value.as_array() {
+                    Some([across, length]) => across
+                        .as_usize()
+                        .zip(length.as_usize())
+                        .filter(|(across, length)| *across > 0 && *length > 0)
+                        .ok_or_else(invalid_capability)?,
+                    _ => return Err(invalid_capability()),
+                }
+            }
+            _ => {
+                return Err(invalid_json(
+                    &format!("{}.{}", path, key),
+                    "isn't a capability",
+                ))
+            }
+        }
+    }
+    // Larger rovers only turn at right angles, so their footprint stays on the grid
+    if profile.diagonal && profile.footprint != (1, 1) {
+        return Err(invalid_json(path, "can't turn 45 degrees with a footprint"));
+    }
+    Ok(profile)
+}
+
+pub fn rover_type_json(profile: &RoverProfile) -> JsonValue {
+    // The same capabilities as in mission files, e.g. '{"speed": 2}'
+    let mut capabilities = Vec::new();
+    if profile.speed != 1 {
+        capabilities.push(("speed".to_string(), JsonValue::Number(profile.speed as f64)));
+    }
+    if let Some(commands) = &profile.allowed_commands {
+        capabilities.push((
+            "commands".to_string(),
+            JsonValue::String(commands.iter().collect()),
+        ));
+    }
+    if profile.diagonal {
+        capabilities.push(("turning".to_string(), JsonValue::Number(45.0)));
+    }
+    if let Some(battery) = profile.battery {
+        capabilities.push(("battery".to_string(), JsonValue::Number(battery as f64)));
+    }
+    if let Some(payload) = profile.payload {
+        capabilities.push(("payload".to_string(), JsonValue::Number(payload as f64)));
+    }
+    if profile.footprint != (1, 1) {
+        capabilities.push((
+            "footprint".to_string(),
+            JsonValue::Array(vec![
+                JsonValue::Number(profile.footprint.0 as f64),
+                JsonValue::Number(profile.footprint.1 as f64),
+            ]),
+        ));
+    }
+    JsonValue::Object(capabilities)
+}
+
+pub fn json_options(
+    options: Option<&JsonValue>,
+    ur_plateau: (i32, i32),
+) -> Result<(MissionOptions, Vec<(String, RoverProfile)>), RoverError> {
+    let mut mission_options = MissionOptions::default();
+    let mut rover_types = Vec::new();
+    let options = match options {
+        Some(options) => options,
+        None => return Ok((mission_options, rover_types)),
+    };
+
+    mission_options.obstacles =
+        json_cells(options.get("obstacles"), ur_plateau, "options.obstacles")?;
+    mission_options.sample_sites =
+        json_cells(options.get("samples"), ur_plateau, "options.samples")?;
+    if let Some(topology) = options.get("topology") {
+        mission_options.topology = match topology.as_str() {
+            Some("square") => Topology::Square,
+            Some("hex") => Topology::Hex,
+            _ => {
+                return Err(invalid_json(
+                    "options.topology",
+                    "must be \"square\" or \"hex\"",
+                ))
+            }
+        };
+    }
+    if let Some(collisions) = options.get("collisions") {
+        mission_options.path_collisions = match collisions.as_str() {
+            Some("final") => false,
+            Some("path") => true,
+            _ => {
+                return Err(invalid_json(
+                    "options.collisions",
+                    "must be \"final\" or \"path\"",
+                ))
+            }
+        };
+    }
+    if let Some(turns) = options.get("diagonal_turns") {
+        let turns = turns.as_array().and_then(|turns| {
+            turns
+                .iter()
+                .map(|turn| turn.as_str())
+                .collect::<Option<Vec<_>>>()
+        });
+        mission_options.diagonal_turns = turns
+            .and_then(|turns| parse_diagonal_turns(&turns))
+            .ok_or_else(|| {
+                invalid_json("options.diagonal_turns", "must be two new command letters")
+            })?;
+    }
+    match options.get("types") {
+        Some(JsonValue::Object(types)) => {
+            for (name, rover_type) in types {
+                let path = format!("options.types.{}", name);
+                rover_types.push((name.to_uppercase(), json_rover_type(rover_type, &path)?));
+            }
+        }
+        Some(_) => return Err(invalid_json("options.types", "must be an object")),
+        None => {}
+    }
+    Ok((mission_options, rover_types))
+}
+
+fn json_strings(strings: Option<&JsonValue>, path: &str) -> Result<Vec<String>, RoverError> {
+    match strings {
+        Some(strings) => strings
+            .as_array()
+            .and_then(|strings| {
+                strings
+                    .iter()
+                    .map(|string| string.as_str().map(|string| string.to_string()))
+                    .collect()
+            })
+            .ok_or_else(|| invalid_json(path, "must be a list of strings")),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub fn parse_json_mission(text: &str) -> Result<Mission, RoverError> {
+    // JSON missions describe the same things as mission files, e.g.
+    // '{"plateau": [5, 5], "rovers": [{"x": 1, "y": 2, "heading": "N", "program": "M"}]}'
+    let json = parse_json(text)?;
+
+    let ur_plateau = match json.get("plateau").and_then(|plateau| plateau.as_array()) {
+        Some([x, y]) => x
+            .as_usize()
+            .zip(y.as_usize())
+            .map(|(x, y)| (x as i32, y as i32))
+            .ok_or(RoverError::InvalidPlateau)?,
+        _ => return Err(RoverError::InvalidPlateau),
+    };
+    let (mut options, rover_types) = json_options(json.get("options"), ur_plateau)?;
+
+    let rovers = match json.get("rovers").and_then(|rovers| rovers.as_array()) {
+        Some(rovers) if !rovers.is_empty() => rovers,
+        _ => return Err(RoverError::InvalidNumberOfCommandsForRover),
+    };
+    let mut rovers_to_deploy = Vec::new();
+    for (index, rover) in rovers.iter().enumerate() {
+        let path = format!("rovers[{}]", index);
+        let field = |key: &str| {
+            rover
+                .get(key)
+                .ok_or_else(|| invalid_json(&format!("{}.{}", path, key), "is missing"))
+        };
+        let invalid_field = |key: &str| invalid_json(&format!("{}.{}", path, key), "is invalid");
+
+        let position_and_heading = PositionAndHeading {
+            x: field("x")?.as_i32().ok_or_else(|| invalid_field("x"))?,
+            y: field("y")?.as_i32().ok_or_else(|| invalid_field("y"))?,
+            heading: field("heading")?
+                .as_str()
+                .and_then(parse_heading)
+                .ok_or_else(|| invalid_field("heading"))?,
+        };
+        let program = match rover.get("program") {
+            Some(program) => program.as_str().ok_or_else(|| invalid_field("program"))?,
+            None => "",
+        };
+        rovers_to_deploy.push((position_and_heading, program.to_string()));
+
+        // Rovers can be given a type by name, or just be able to drive diagonally
+        let rover_type = match rover.get("type") {
+            Some(name) => {
+                let name = name
+                    .as_str()
+                    .ok_or_else(|| invalid_field("type"))?
+                    .to_uppercase();
+                let (_, profile) =
+                    rover_types
+                        .iter()
+                        .find(|(n, _)| *n == name)
+                        .ok_or_else(|| {
+                            invalid_json(&format!("{}.type", path), "isn't a declared type")
+                        })?;
+                Some(profile.clone())
+            }
+            None => None,
+        };
+        let diagonal = match rover.get("diagonal") {
+            Some(diagonal) => diagonal
+                .as_bool()
+                .ok_or_else(|| invalid_field("diagonal"))?,
+            None => false,
+        };
+        if rover_type.is_some() || diagonal {
+            let mut profile = rover_type.unwrap_or_default();
+            profile.diagonal |= diagonal;
+            options
+                .rover_profiles
+                .resize(index + 1, RoverProfile::default());
+            options.rover_profiles[index] = profile;
+        }
+    }
+
+    let objectives = json_strings(json.get("objectives"), "objectives")?
+        .iter()
+        .enumerate()
+        .map(|(index, objective)| {
+            let words = objective.split_whitespace().collect::<Vec<_>>();
+            parse_objective(&words, ur_plateau, rovers_to_deploy.len())
+                .ok_or_else(|| invalid_json(&format!("objectives[{}]", index), "is invalid"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let expectations = json_strings(json.get("expect"), "expect")?
+        .iter()
+        .enumerate()
+        .map(|(index, outcome)| {
+            parse_outcome(outcome)
+                .ok_or_else(|| invalid_json(&format!("expect[{}]", index), "is invalid"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if expectations.len() > rovers_to_deploy.len() {
+        return Err(invalid_json("expect", "has more outcomes than rovers"));
+    }
+
+    Ok(Mission {
+        input_command: InputCommand {
+            ur_plateau,
+            rovers_to_deploy,
+        },
+        options,
+        objectives,
+        expectations,
+    })
+}
+
+pub fn simulate_mission(mission: Mission) -> Result<Vec<PositionAndHeading>, RoverError> {
+    simulate_rover_move_with_options(mission.input_command, &mission.options)
+}
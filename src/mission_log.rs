@@ -0,0 +1,334 @@
+use crate::check::{error_name, rover_outcomes, Outcome};
+use crate::json::{format_json, parse_json, JsonValue};
+use crate::mission::{invalid_json, Mission};
+use crate::rover::{
+    get_step, heading_name, parse_heading, MissionOptions, PositionAndHeading, RoverError,
+    RoverReport, SimulationResult,
+};
+
+// Rovers are numbered from 0 in the order they're deployed
+#[derive(Debug, PartialEq, Clone)]
+pub enum MissionEvent {
+    Deployed {
+        rover: usize,
+        position: PositionAndHeading,
+    },
+    // Turned on the spot
+    Turned {
+        rover: usize,
+        command: char,
+        position: PositionAndHeading,
+    },
+    // Drove to another cell
+    Moved {
+        rover: usize,
+        command: char,
+        position: PositionAndHeading,
+    },
+    // Ran a command that left the rover as it was, such as collecting a sample
+    Stayed {
+        rover: usize,
+        command: char,
+        position: PositionAndHeading,
+    },
+    // Stopped by an error, where the rover was when it happened
+    Blocked {
+        rover: usize,
+        position: PositionAndHeading,
+        error: String,
+    },
+    Finished {
+        rover: usize,
+        position: PositionAndHeading,
+    },
+}
+
+fn step_event(
+    rover: usize,
+    command: char,
+    before: &PositionAndHeading,
+    position: PositionAndHeading,
+) -> MissionEvent {
+    if (before.x, before.y) != (position.x, position.y) {
+        MissionEvent::Moved {
+            rover,
+            command,
+            position,
+        }
+    } else if before.heading != position.heading {
+        MissionEvent::Turned {
+            rover,
+            command,
+            position,
+        }
+    } else {
+        MissionEvent::Stayed {
+            rover,
+            command,
+            position,
+        }
+    }
+}
+
+fn rover_events(rover: usize, report: &RoverReport, events: &mut Vec<MissionEvent>) {
+    let trajectory = &report.trajectory;
+    events.push(MissionEvent::Deployed {
+        rover,
+        position: trajectory[0].clone(),
+    });
+    for (step, command) in trajectory.windows(2).zip(&report.commands) {
+        events.push(step_event(rover, *command, &step[0], step[1].clone()));
+    }
+}
+
+fn step_parts(event: &MissionEvent) -> Option<(usize, char, PositionAndHeading)> {
+    match event {
+        MissionEvent::Turned {
+            rover,
+            command,
+            position,
+        }
+        | MissionEvent::Moved {
+            rover,
+            command,
+            position,
+        }
+        | MissionEvent::Stayed {
+            rover,
+            command,
+            position,
+        } => Some((*rover, *command, position.clone())),
+        _ => None,
+    }
+}
+
+pub fn mission_events(result: &SimulationResult) -> Vec<MissionEvent> {
+    let mut events = Vec::new();
+    for (rover, report) in result.reports.iter().enumerate() {
+        rover_events(rover, report, &mut events);
+        events.push(MissionEvent::Finished {
+            rover,
+            position: report.trajectory[report.trajectory.len() - 1].clone(),
+        });
+    }
+    if let Some(failure) = &result.failure {
+        let trajectory = &failure.report.trajectory;
+        rover_events(failure.rover, &failure.report, &mut events);
+        events.push(MissionEvent::Blocked {
+            rover: failure.rover,
+            position: trajectory[trajectory.len() - 1].clone(),
+            error: error_name(&failure.error),
+        });
+    }
+    events
+}
+
+pub fn format_event(event: &MissionEvent) -> String {
+    // A record per line, e.g. '{"event":"moved","rover":1,"command":"M","x":1,"y":3,"heading":"N"}'
+    let (name, rover, position) = match event {
+        MissionEvent::Deployed { rover, position } => ("deployed", rover, position),
+        MissionEvent::Turned {
+            rover, position, ..
+        } => ("turned", rover, position),
+        MissionEvent::Moved {
+            rover, position, ..
+        } => ("moved", rover, position),
+        MissionEvent::Stayed {
+            rover, position, ..
+        } => ("stayed", rover, position),
+        MissionEvent::Blocked {
+            rover, position, ..
+        } => ("blocked", rover, position),
+        MissionEvent::Finished { rover, position } => ("finished", rover, position),
+    };
+    let mut members = vec![
+        ("event".to_string(), JsonValue::String(name.to_string())),
+        ("rover".to_string(), JsonValue::Number((rover + 1) as f64)),
+    ];
+    if let Some((_, command, _)) = step_parts(event) {
+        members.push((
+            "command".to_string(),
+            JsonValue::String(command.to_string()),
+        ));
+    }
+    members.extend(vec![
+        ("x".to_string(), JsonValue::Number(position.x as f64)),
+        ("y".to_string(), JsonValue::Number(position.y as f64)),
+        (
+            "heading".to_string(),
+            JsonValue::String(heading_name(position.heading)),
+        ),
+    ]);
+    if let MissionEvent::Blocked { error, .. } = event {
+        members.push(("error".to_string(), JsonValue::String(error.clone())));
+    }
+    format_json(&JsonValue::Object(members))
+}
+
+pub fn format_events(events: &[MissionEvent]) -> String {
+    events
+        .iter()
+        .map(|event| format!("{}\n", format_event(event)))
+        .collect()
+}
+
+pub fn parse_event(line: &str) -> Result<MissionEvent, RoverError> {
+    let record = parse_json(line)?;
+    let field = |key: &str| {
+        record
+            .get(key)
+            .ok_or_else(|| invalid_json(key, "is missing"))
+    };
+    let text_field = |key: &str| {
+        field(key)?
+            .as_str()
+            .map(|text| text.to_string())
+            .ok_or_else(|| invalid_json(key, "must be text"))
+    };
+    let number_field = |key: &str| {
+        field(key)?
+            .as_i32()
+            .ok_or_else(|| invalid_json(key, "must be a whole number"))
+    };
+
+    let rover = match field("rover")?.as_usize() {
+        Some(rover) if rover >= 1 => rover - 1,
+        _ => return Err(invalid_json("rover", "must be a rover number")),
+    };
+    let position = PositionAndHeading {
+        x: number_field("x")?,
+        y: number_field("y")?,
+        heading: parse_heading(&text_field("heading")?)
+            .ok_or_else(|| invalid_json("heading", "is invalid"))?,
+    };
+    let command_field = || {
+        let command = text_field("command")?;
+        let mut letters = command.chars();
+        match (letters.next(), letters.next()) {
+            (Some(letter), None) => Ok(letter),
+            _ => Err(invalid_json("command", "must be a single letter")),
+        }
+    };
+    match text_field("event")?.as_str() {
+        "deployed" => Ok(MissionEvent::Deployed { rover, position }),
+        "turned" => Ok(MissionEvent::Turned {
+            rover,
+            command: command_field()?,
+            position,
+        }),
+        "moved" => Ok(MissionEvent::Moved {
+            rover,
+            command: command_field()?,
+            position,
+        }),
+        "stayed" => Ok(MissionEvent::Stayed {
+            rover,
+            command: command_field()?,
+            position,
+        }),
+        "blocked" => Ok(MissionEvent::Blocked {
+            rover,
+            position,
+            error: text_field("error")?,
+        }),
+        "finished" => Ok(MissionEvent::Finished { rover, position }),
+        _ => Err(invalid_json("event", "is invalid")),
+    }
+}
+
+fn line_error(line: usize, problem: &str) -> RoverError {
+    RoverError::InvalidJson(format!("line {}: {}", line, problem))
+}
+
+pub fn replay(log: &str, options: &MissionOptions) -> Result<Vec<Outcome>, RoverError> {
+    // Logs are appended to, so only the last run counts. A run starts when the first
+    // rover is deployed, and each rover's events must follow on from the one before,
+    // every step being one its command could have made under the mission's options
+    let mut outcomes = Vec::new();
+    let mut current: Option<(usize, PositionAndHeading)> = None;
+    let mut last_line = 0;
+
+    for (index, line) in log.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        last_line = index + 1;
+        let out_of_order = || line_error(last_line, "the event is out of order");
+        let event = parse_event(line).map_err(|err| match err {
+            RoverError::InvalidJson(problem) => line_error(last_line, &problem),
+            err => err,
+        })?;
+
+        if let Some((rover, command, position)) = step_parts(&event) {
+            let last = match &mut current {
+                Some((active, last)) if rover == *active => last,
+                _ => return Err(out_of_order()),
+            };
+            // Custom commands can put the rover anywhere and may do more than move it,
+            // so they're only checked against the mission's commands, never run again
+            let next = if options.commands.get(command).is_some() {
+                Some(position.clone())
+            } else {
+                get_step(last, command, rover, options)
+            };
+            let expected = next.map(|next| step_event(rover, command, last, next));
+            if expected != Some(event) {
+                return Err(line_error(
+                    last_line,
+                    &format!(
+                        "rover {} can't make that step with '{}'",
+                        rover + 1,
+                        command
+                    ),
+                ));
+            }
+            *last = position;
+            continue;
+        }
+
+        match (event, &mut current) {
+            (MissionEvent::Deployed { rover: 0, position }, _) => {
+                outcomes.clear();
+                current = Some((0, position));
+            }
+            (MissionEvent::Deployed { rover, position }, None) if rover == outcomes.len() => {
+                current = Some((rover, position));
+            }
+            (MissionEvent::Finished { rover, position }, Some((active, last)))
+                if rover == *active && position == *last =>
+            {
+                outcomes.push(Outcome::Finished(position));
+                current = None;
+            }
+            (
+                MissionEvent::Blocked {
+                    rover,
+                    position,
+                    error,
+                },
+                Some((active, last)),
+            ) if rover == *active && position == *last => {
+                outcomes.push(Outcome::Failed(error));
+                current = None;
+            }
+            _ => return Err(out_of_order()),
+        }
+    }
+
+    // A rover still driving means the log was cut short
+    if let Some((rover, _)) = current {
+        return Err(line_error(
+            last_line,
+            &format!("the log ends before rover {} finished", rover + 1),
+        ));
+    }
+    Ok(outcomes)
+}
+
+pub fn verify_log(log: &str, mission: &Mission) -> Result<bool, RoverError> {
+    // Rovers after one that failed were never deployed, so aren't in the log
+    let mut logged = replay(log, &mission.options)?;
+    let simulated = rover_outcomes(mission)?;
+    logged.resize(simulated.len().max(logged.len()), Outcome::NotDeployed);
+    Ok(logged == simulated)
+}
@@ -0,0 +1,156 @@
+use crate::rover::{heading_name, RoverReport, SimulationResult};
+
+// Rovers are numbered from 0 in the order they're deployed
+#[derive(Debug, PartialEq, Clone)]
+pub enum Objective {
+    // Finish on the cell
+    Reach {
+        rover: usize,
+        cell: (i32, i32),
+    },
+    // Finish facing the heading
+    Heading {
+        rover: usize,
+        heading: char,
+    },
+    // Drive through the cells in order
+    Waypoints {
+        rover: usize,
+        cells: Vec<(i32, i32)>,
+    },
+    // Collect at least this many samples
+    Samples {
+        rover: usize,
+        count: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ObjectiveResult {
+    pub objective: Objective,
+    pub satisfied: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScoreReport {
+    pub results: Vec<ObjectiveResult>,
+    pub score: usize, // Objectives satisfied, out of all the objectives
+    pub passed: bool, // Every objective was satisfied
+}
+
+fn visits_in_order(report: &RoverReport, cells: &[(i32, i32)]) -> bool {
+    // Each waypoint has to be driven through after the one before it
+    let mut trajectory = report.trajectory.iter();
+    cells.iter().all(|cell| {
+        trajectory
+            .by_ref()
+            .any(|position| (position.x, position.y) == *cell)
+    })
+}
+
+fn is_satisfied(objective: &Objective, reports: &[RoverReport]) -> bool {
+    let rover = match objective {
+        Objective::Reach { rover, .. }
+        | Objective::Heading { rover, .. }
+        | Objective::Waypoints { rover, .. }
+        | Objective::Samples { rover, .. } => *rover,
+    };
+    // Rovers that never finished their programs can't satisfy anything
+    let report = match reports.get(rover) {
+        Some(report) => report,
+        None => return false,
+    };
+    let final_position = &report.trajectory[report.trajectory.len() - 1];
+
+    match objective {
+        Objective::Reach { cell, .. } => (final_position.x, final_position.y) == *cell,
+        Objective::Heading { heading, .. } => final_position.heading == *heading,
+        Objective::Waypoints { cells, .. } => visits_in_order(report, cells),
+        Objective::Samples { count, .. } => report.samples.len() >= *count,
+    }
+}
+
+pub fn score_objectives(objectives: &[Objective], reports: &[RoverReport]) -> ScoreReport {
+    let results = objectives
+        .iter()
+        .map(|objective| ObjectiveResult {
+            objective: objective.clone(),
+            satisfied: is_satisfied(objective, reports),
+        })
+        .collect::<Vec<_>>();
+    let score = results.iter().filter(|result| result.satisfied).count();
+
+    ScoreReport {
+        passed: score == results.len(),
+        results,
+        score,
+    }
+}
+
+pub fn score_mission(objectives: &[Objective], result: &SimulationResult) -> ScoreReport {
+    // A rover stopping with an error misses every objective, even those of rovers
+    // that finished before it
+    match result.failure {
+        Some(_) => score_objectives(objectives, &[]),
+        None => score_objectives(objectives, &result.reports),
+    }
+}
+
+fn format_cells(cells: &[(i32, i32)]) -> String {
+    cells
+        .iter()
+        .map(|(x, y)| format!("{} {}", x, y))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn describe_objective(objective: &Objective) -> String {
+    match objective {
+        Objective::Reach { rover, cell } => {
+            format!("Rover {} finishes on {} {}", rover + 1, cell.0, cell.1)
+        }
+        Objective::Heading { rover, heading } => {
+            format!(
+                "Rover {} finishes facing {}",
+                rover + 1,
+                heading_name(*heading)
+            )
+        }
+        Objective::Waypoints { rover, cells } => {
+            format!("Rover {} drives through {}", rover + 1, format_cells(cells))
+        }
+        Objective::Samples { rover, count } => {
+            format!("Rover {} collects {} sample(s)", rover + 1, count)
+        }
+    }
+}
+
+pub fn format_score_report(score_report: &ScoreReport) -> String {
+    let mut formatted = String::new();
+    for result in &score_report.results {
+        let status = if result.satisfied { "PASS" } else { "FAIL" };
+        formatted.push_str(&format!(
+            "[{}] {}\n",
+            status,
+            describe_objective(&result.objective)
+        ));
+    }
+    formatted.push_str(&format!(
+        "\nScore: {}/{} ({})\n",
+        score_report.score,
+        score_report.results.len(),
+        if score_report.passed { "PASS" } else { "FAIL" }
+    ));
+    formatted
+}
+
+pub fn format_objectives_report(score_report: &ScoreReport) -> String {
+    format!(
+        "\nMission objective(s):\n\n{}",
+        format_score_report(score_report)
+    )
+}
+
+pub fn print_score_report(score_report: &ScoreReport) {
+    print!("{}", format_objectives_report(score_report));
+}
@@ -0,0 +1,102 @@
+use crate::program::expand_program;
+use crate::rover::{MissionOptions, RoverError, Topology};
+
+fn push_net_turn(
+    optimized: &mut String,
+    net_rotation: i32,
+    first_turn: Option<char>,
+    full_turn: i32,
+) {
+    // Turns clockwise out of a full turn, 4 on a square plateau and 6 on a hex one,
+    // so 3 right turns on a square plateau are the same as a single left
+    let rotation = net_rotation.rem_euclid(full_turn);
+    let (turn, count) = if rotation * 2 == full_turn {
+        // A half turn is the same length either way, keep the direction first used
+        (first_turn.unwrap_or('L'), rotation)
+    } else if rotation * 2 < full_turn {
+        ('R', rotation)
+    } else {
+        ('L', full_turn - rotation)
+    };
+    for _ in 0..count {
+        optimized.push(turn);
+    }
+}
+
+pub fn optimize_program(program: &str) -> Result<String, RoverError> {
+    optimize_program_with_topology(program, Topology::Square)
+}
+
+pub fn optimize_program_with_topology(
+    program: &str,
+    topology: Topology,
+) -> Result<String, RoverError> {
+    let full_turn = match topology {
+        Topology::Square => 4,
+        Topology::Hex => 6,
+    };
+    let mut optimized = String::new();
+    let mut net_rotation = 0;
+    let mut first_turn = None;
+
+    // Normalize and expand the program the same way the simulator does before reading it
+    for command in expand_program(&program.to_uppercase())?.chars() {
+        match command {
+            'L' | 'R' => {
+                net_rotation += if command == 'R' { 1 } else { -1 };
+                first_turn = first_turn.or(Some(command));
+            }
+            'U' => {
+                // A U-turn has no direction of its own, so it doesn't set one
+                net_rotation += full_turn / 2;
+            }
+            'S' | 'M' | 'B' => {
+                // Only consecutive turns are folded, so every other command runs
+                // facing the same way as it did before
+                push_net_turn(&mut optimized, net_rotation, first_turn, full_turn);
+                optimized.push(command);
+                net_rotation = 0;
+                first_turn = None;
+            }
+            _ => return Err(RoverError::InvalidMove),
+        }
+    }
+    // Trailing turns still decide the final heading
+    push_net_turn(&mut optimized, net_rotation, first_turn, full_turn);
+
+    Ok(optimized)
+}
+
+// Optimizes the program of the rover deployed at `rover` (counting from 0), or returns
+// None when it has to be left as it is
+pub fn optimize_rover_program(
+    program: &str,
+    options: &MissionOptions,
+    rover: usize,
+) -> Option<String> {
+    let profile = options
+        .rover_profiles
+        .get(rover)
+        .cloned()
+        .unwrap_or_default();
+    // Folding turns saves battery, so a rover that would run flat could finish instead,
+    // and a larger rover covers other cells after each turn, which are all checked
+    if profile.battery.is_some() || profile.footprint != (1, 1) {
+        return None;
+    }
+
+    // Invalid programs are left as they are so the simulator can report them
+    let optimized_program = optimize_program_with_topology(program, options.topology).ok()?;
+
+    // Merging turns can swap one turn command for the other, which some rover
+    // types can't run
+    if let Some(allowed_commands) = &profile.allowed_commands {
+        if !optimized_program
+            .chars()
+            .all(|c| allowed_commands.contains(&c))
+        {
+            return None;
+        }
+    }
+    Some(optimized_program)
+}
@@ -0,0 +1,187 @@
+use crate::check::{error_name, format_outcome, Outcome};
+use crate::json::{format_json, JsonValue};
+use crate::rover::{heading_name, RoverError};
+
+// Turns each rover's outcome into text, so results can be written anywhere,
+// not just to the terminal
+pub trait OutputFormatter {
+    fn format(&self, outcomes: &[Outcome]) -> String;
+
+    // The outcomes as the results of a whole run, e.g. with a title for text
+    fn format_results(&self, outcomes: &[Outcome]) -> String {
+        self.format(outcomes)
+    }
+
+    // A mission that couldn't be run, e.g. one with an invalid plateau
+    fn format_error(&self, err: &RoverError) -> String;
+}
+
+// One line per rover, as printed after a simulation, e.g. '1 3 N' or 'error Collision'
+pub struct TextFormatter;
+
+// An array with an object per rover, e.g.
+// [{"index":1,"x":1,"y":3,"heading":"N","status":"ok"}]
+pub struct JsonFormatter;
+
+// A header row, then a row per rover, e.g. 'index,x,y,heading,status'
+pub struct CsvFormatter;
+
+// The status is 'ok', the name of the error the rover stopped with, or 'not deployed'
+fn status(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Finished(_) => "ok".to_string(),
+        Outcome::Failed(name) => name.clone(),
+        Outcome::NotDeployed => "not deployed".to_string(),
+    }
+}
+
+// What to check to fix the error, e.g. 'your obstacle(s)'
+fn error_hint(err: &RoverError) -> &'static str {
+    match err {
+        RoverError::InvalidPlateau => "plateau coordinates",
+        RoverError::InvalidMacro { .. } | RoverError::RecursiveMacro { .. } => {
+            "your macro definition(s)"
+        }
+        RoverError::InvalidObstacle { .. } => "your obstacle(s)",
+        RoverError::InvalidOption { .. } => "your mission option(s)",
+        RoverError::InvalidJson(_) => "your JSON mission",
+        _ => "your rover command(s)",
+    }
+}
+
+// A readable account of the error for other programs to show, e.g. 'the plateau
+// coordinates are invalid'
+pub fn error_detail(err: &RoverError) -> String {
+    match err {
+        RoverError::OutOfBounds => "a rover drove off the plateau".to_string(),
+        RoverError::Collision => "a rover ran into an obstacle or another rover".to_string(),
+        RoverError::InvalidHeading => "a rover's heading is invalid".to_string(),
+        RoverError::InvalidMove => "a rover's program is invalid".to_string(),
+        RoverError::InvalidStartMove => "a rover's start coordinates are invalid".to_string(),
+        RoverError::InvalidStartPosition => "a rover's start position is invalid".to_string(),
+        RoverError::InvalidPlateau => "the plateau coordinates are invalid".to_string(),
+        RoverError::InvalidNumberOfCommandsForRover => {
+            "every rover needs a start position and a program".to_string()
+        }
+        RoverError::StartOutOfBounds => "a rover starts off the plateau".to_string(),
+        RoverError::ProgramTooLong => "a rover's program is too long".to_string(),
+        RoverError::InvalidMacro { name, line } => {
+            format!("the macro '{}' on line {} is invalid", name, line)
+        }
+        RoverError::RecursiveMacro { name, line } => {
+            format!("the macro '{}' on line {} uses itself", name, line)
+        }
+        RoverError::UndefinedMacro(name) => format!("the macro '{}' isn't defined", name),
+        RoverError::InvalidObstacle { line } => {
+            format!("the obstacle on line {} is invalid", line)
+        }
+        RoverError::InvalidOption { line } => format!("the option on line {} is invalid", line),
+        RoverError::InvalidCommand(command) => format!("'{}' isn't a command", command),
+        RoverError::StepLimitExceeded => "a rover's program ran for too many steps".to_string(),
+        RoverError::BatteryDepleted => "a rover's battery ran flat".to_string(),
+        RoverError::InvalidFootprint => "a rover's footprint is invalid".to_string(),
+        RoverError::PayloadFull => "a rover's payload is full".to_string(),
+        RoverError::InvalidJson(problem) => problem.clone(),
+        RoverError::InvalidTopology => "this is only available on square plateaus".to_string(),
+    }
+}
+
+impl OutputFormatter for TextFormatter {
+    fn format(&self, outcomes: &[Outcome]) -> String {
+        outcomes
+            .iter()
+            .map(|outcome| format!("{}\n", format_outcome(outcome)))
+            .collect()
+    }
+
+    fn format_results(&self, outcomes: &[Outcome]) -> String {
+        format!("\nFinal rover position(s):\n\n{}", self.format(outcomes))
+    }
+
+    fn format_error(&self, err: &RoverError) -> String {
+        format!("Error: {:?}. Please check {}.\n", err, error_hint(err))
+    }
+}
+
+pub fn error_json(name: &str, detail: &str) -> JsonValue {
+    // Errors are given as '{"error":{"name":"InvalidPlateau","detail":"..."}}'
+    let error = JsonValue::Object(vec![
+        ("name".to_string(), JsonValue::String(name.to_string())),
+        ("detail".to_string(), JsonValue::String(detail.to_string())),
+    ]);
+    JsonValue::Object(vec![("error".to_string(), error)])
+}
+
+pub fn outcomes_json(outcomes: &[Outcome]) -> JsonValue {
+    // Rovers without a final position have null coordinates and heading
+    let rovers = outcomes
+        .iter()
+        .enumerate()
+        .map(|(index, outcome)| {
+            let (x, y, heading) = match outcome {
+                Outcome::Finished(position) => (
+                    JsonValue::Number(position.x as f64),
+                    JsonValue::Number(position.y as f64),
+                    JsonValue::String(heading_name(position.heading)),
+                ),
+                _ => (JsonValue::Null, JsonValue::Null, JsonValue::Null),
+            };
+            JsonValue::Object(vec![
+                ("index".to_string(), JsonValue::Number((index + 1) as f64)),
+                ("x".to_string(), x),
+                ("y".to_string(), y),
+                ("heading".to_string(), heading),
+                ("status".to_string(), JsonValue::String(status(outcome))),
+            ])
+        })
+        .collect();
+    JsonValue::Array(rovers)
+}
+
+impl OutputFormatter for JsonFormatter {
+    fn format(&self, outcomes: &[Outcome]) -> String {
+        format!("{}\n", format_json(&outcomes_json(outcomes)))
+    }
+
+    fn format_error(&self, err: &RoverError) -> String {
+        let detail = error_detail(err);
+        format!("{}\n", format_json(&error_json(&error_name(err), &detail)))
+    }
+}
+
+impl OutputFormatter for CsvFormatter {
+    fn format(&self, outcomes: &[Outcome]) -> String {
+        // Rovers without a final position leave those columns empty
+        let mut formatted = String::from("index,x,y,heading,status\n");
+        for (index, outcome) in outcomes.iter().enumerate() {
+            let position = match outcome {
+                Outcome::Finished(position) => {
+                    format!(
+                        "{},{},{}",
+                        position.x,
+                        position.y,
+                        heading_name(position.heading)
+                    )
+                }
+                _ => ",,".to_string(),
+            };
+            formatted.push_str(&format!("{},{},{}\n", index + 1, position, status(outcome)));
+        }
+        formatted
+    }
+
+    fn format_error(&self, err: &RoverError) -> String {
+        // The detail is quoted, as it can hold commas
+        let detail = error_detail(err).replace('"', "\"\"");
+        format!("error,detail\n{},\"{}\"\n", error_name(err), detail)
+    }
+}
+
+pub fn get_formatter(name: &str) -> Option<Box<dyn OutputFormatter>> {
+    match name {
+        "text" => Some(Box::new(TextFormatter)),
+        "json" => Some(Box::new(JsonFormatter)),
+        "csv" => Some(Box::new(CsvFormatter)),
+        _ => None,
+    }
+}
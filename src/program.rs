@@ -0,0 +1,288 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::rover::RoverError;
+
+// Upper limit on the number of commands a program may expand to, so a few
+// nested repetitions can't exhaust memory
+pub const MAX_PROGRAM_LENGTH: usize = 100_000;
+
+// Upper limit on the commands and loop iterations a rover may run, so every
+// program finishes even if its loops never would
+pub const MAX_STEPS: usize = 1_000_000;
+
+// Groups, blocks, counts and conditions nested deeper than this are rejected
+// rather than risking the stack
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Condition {
+    Blocked, // The cell ahead is off the plateau, an obstacle or another rover
+    Edge,    // The cell ahead is off the plateau
+    Not(Box<Condition>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Instruction {
+    Command(char),
+    Repeat(usize, Vec<Instruction>),
+    If(Condition, Vec<Instruction>, Vec<Instruction>),
+    While(Condition, Vec<Instruction>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Command(char),
+    Count(usize),
+    OpenGroup,
+    CloseGroup,
+    GroupCount(usize), // A count straight after a closing bracket, with nothing between
+    OpenBlock,
+    CloseBlock,
+    Not,
+    If,
+    Else,
+    While,
+    Blocked,
+    Edge,
+}
+
+fn read_count(mut count: String, chars: &mut Peekable<Chars>) -> usize {
+    while let Some(digit) = chars.peek().filter(|d| d.is_ascii_digit()) {
+        count.push(*digit);
+        chars.next();
+    }
+    // Counts too big to hold are far beyond any limit anyway
+    count.parse().unwrap_or(usize::MAX)
+}
+
+fn tokenize(program: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = program.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::OpenGroup),
+            ')' => {
+                tokens.push(Token::CloseGroup);
+                if chars.peek().is_some_and(|d| d.is_ascii_digit()) {
+                    tokens.push(Token::GroupCount(read_count(String::new(), &mut chars)));
+                }
+            }
+            '{' => tokens.push(Token::OpenBlock),
+            '}' => tokens.push(Token::CloseBlock),
+            '!' => tokens.push(Token::Not),
+            c if c.is_ascii_digit() => {
+                tokens.push(Token::Count(read_count(c.to_string(), &mut chars)))
+            }
+            c if c.is_alphabetic() => {
+                let mut word = c.to_uppercase().to_string();
+                while let Some(letter) = chars.peek().filter(|l| l.is_alphabetic()) {
+                    word.extend(letter.to_uppercase());
+                    chars.next();
+                }
+                // Keywords are whole words, anything else is a run of commands
+                match word.as_str() {
+                    "IF" => tokens.push(Token::If),
+                    "ELSE" => tokens.push(Token::Else),
+                    "WHILE" => tokens.push(Token::While),
+                    "BLOCKED" => tokens.push(Token::Blocked),
+                    "EDGE" => tokens.push(Token::Edge),
+                    _ => tokens.extend(word.chars().map(Token::Command)),
+                }
+            }
+            // Help the user by skipping spaces and any other stray chars
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn check_depth(depth: usize) -> Result<(), RoverError> {
+    if depth > MAX_NESTING_DEPTH {
+        Err(RoverError::ProgramTooLong)
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_condition(
+    tokens: &[Token],
+    index: &mut usize,
+    depth: usize,
+) -> Result<Condition, RoverError> {
+    check_depth(depth)?;
+    let token = tokens.get(*index).ok_or(RoverError::InvalidMove)?;
+    *index += 1;
+    match token {
+        Token::Not => Ok(Condition::Not(Box::new(parse_condition(
+            tokens,
+            index,
+            depth + 1,
+        )?))),
+        Token::Blocked => Ok(Condition::Blocked),
+        Token::Edge => Ok(Condition::Edge),
+        _ => Err(RoverError::InvalidMove),
+    }
+}
+
+fn parse_block(
+    tokens: &[Token],
+    index: &mut usize,
+    depth: usize,
+) -> Result<Vec<Instruction>, RoverError> {
+    if tokens.get(*index) != Some(&Token::OpenBlock) {
+        return Err(RoverError::InvalidMove);
+    }
+    *index += 1;
+    let block = parse_sequence(tokens, index, depth + 1)?;
+    if tokens.get(*index) != Some(&Token::CloseBlock) {
+        return Err(RoverError::InvalidMove);
+    }
+    *index += 1;
+    Ok(block)
+}
+
+fn parse_group(
+    tokens: &[Token],
+    index: &mut usize,
+    depth: usize,
+) -> Result<(Vec<Instruction>, Option<usize>), RoverError> {
+    // Called just past the opening bracket. A count straight after the closing
+    // bracket, e.g. '(MR)2', repeats the whole group
+    let group = parse_sequence(tokens, index, depth + 1)?;
+    if tokens.get(*index) != Some(&Token::CloseGroup) {
+        return Err(RoverError::InvalidMove);
+    }
+    *index += 1;
+    match tokens.get(*index) {
+        Some(Token::GroupCount(count)) => {
+            *index += 1;
+            Ok((group, Some(*count)))
+        }
+        _ => Ok((group, None)),
+    }
+}
+
+fn parse_instruction(
+    tokens: &[Token],
+    index: &mut usize,
+    depth: usize,
+) -> Result<Instruction, RoverError> {
+    check_depth(depth)?;
+    let token = tokens.get(*index).ok_or(RoverError::InvalidMove)?;
+    *index += 1;
+    match token {
+        Token::Command(command) => Ok(Instruction::Command(*command)),
+        Token::OpenGroup => {
+            let (group, count) = parse_group(tokens, index, depth)?;
+            Ok(Instruction::Repeat(count.unwrap_or(1), group))
+        }
+        Token::Count(count) if tokens.get(*index) == Some(&Token::OpenGroup) => {
+            // A group can be counted before or after, e.g. '2(ML)' or '(ML)2', not both
+            *index += 1;
+            match parse_group(tokens, index, depth)? {
+                (group, None) => Ok(Instruction::Repeat(*count, group)),
+                (_, Some(_)) => Err(RoverError::InvalidMove),
+            }
+        }
+        Token::Count(count) => {
+            // Counts come before the instruction they repeat, e.g. '16M'
+            let instruction = parse_instruction(tokens, index, depth + 1)?;
+            Ok(Instruction::Repeat(*count, vec![instruction]))
+        }
+        Token::If => {
+            let condition = parse_condition(tokens, index, depth + 1)?;
+            let then_block = parse_block(tokens, index, depth)?;
+            let else_block = if tokens.get(*index) == Some(&Token::Else) {
+                *index += 1;
+                parse_block(tokens, index, depth)?
+            } else {
+                Vec::new()
+            };
+            Ok(Instruction::If(condition, then_block, else_block))
+        }
+        Token::While => {
+            let condition = parse_condition(tokens, index, depth + 1)?;
+            Ok(Instruction::While(
+                condition,
+                parse_block(tokens, index, depth)?,
+            ))
+        }
+        _ => Err(RoverError::InvalidMove),
+    }
+}
+
+fn parse_sequence(
+    tokens: &[Token],
+    index: &mut usize,
+    depth: usize,
+) -> Result<Vec<Instruction>, RoverError> {
+    check_depth(depth)?;
+    let mut instructions = Vec::new();
+    while let Some(token) = tokens.get(*index) {
+        if matches!(token, Token::CloseGroup | Token::CloseBlock) {
+            break;
+        }
+        instructions.push(parse_instruction(tokens, index, depth)?);
+    }
+    Ok(instructions)
+}
+
+pub fn parse_program(program: &str) -> Result<Vec<Instruction>, RoverError> {
+    let tokens = tokenize(program);
+    let mut index = 0;
+    let instructions = parse_sequence(&tokens, &mut index, 0)?;
+    // Stopping early means a bracket was closed without being opened
+    if index < tokens.len() {
+        return Err(RoverError::InvalidMove);
+    }
+    Ok(instructions)
+}
+
+pub fn program_commands(instructions: &[Instruction]) -> String {
+    // Every command letter the program mentions, whether or not it would run
+    let mut commands = String::new();
+    for instruction in instructions {
+        match instruction {
+            Instruction::Command(command) => commands.push(*command),
+            Instruction::Repeat(_, body) | Instruction::While(_, body) => {
+                commands.push_str(&program_commands(body))
+            }
+            Instruction::If(_, then_block, else_block) => {
+                commands.push_str(&program_commands(then_block));
+                commands.push_str(&program_commands(else_block));
+            }
+        }
+    }
+    commands
+}
+
+fn flatten_instructions(instructions: &[Instruction]) -> Result<String, RoverError> {
+    let mut expanded = String::new();
+    for instruction in instructions {
+        let commands = match instruction {
+            Instruction::Command(command) => command.to_string(),
+            Instruction::Repeat(count, body) => {
+                let body = flatten_instructions(body)?;
+                match body.len().checked_mul(*count) {
+                    Some(length) if length <= MAX_PROGRAM_LENGTH => body.repeat(*count),
+                    _ => return Err(RoverError::ProgramTooLong),
+                }
+            }
+            // What runs depends on the rover's surroundings, so can't be known up front
+            Instruction::If(..) | Instruction::While(..) => return Err(RoverError::InvalidMove),
+        };
+
+        if expanded.len() + commands.len() > MAX_PROGRAM_LENGTH {
+            return Err(RoverError::ProgramTooLong);
+        }
+        expanded.push_str(&commands);
+    }
+    Ok(expanded)
+}
+
+pub fn expand_program(program: &str) -> Result<String, RoverError> {
+    // Only programs without conditions or loops expand to a fixed list of commands
+    flatten_instructions(&parse_program(program)?)
+}
@@ -0,0 +1,223 @@
+use crate::check::rover_outcomes;
+use crate::history::History;
+use crate::mission::{is_directive, parse_mission};
+use crate::output::{OutputFormatter, TextFormatter};
+use crate::rover::{heading_name, parse_heading, PositionAndHeading};
+
+pub const REPL_HELP: &str = "\
+:plateau X Y               set the upper right corner of the plateau
+:rover X Y HEADING PROGRAM add a rover, e.g. ':rover 1 2 N LMLMLMLMM'
+:option DIRECTIVE          set up the mission, e.g. ':option obstacle 2 2'
+:list                      show the plateau and rovers entered
+:delete N                  remove rover N
+:run                       simulate the rovers entered
+:reset                     remove the plateau, every rover and every option
+:undo [N]                  undo the last change, or the last N
+:redo [N]                  redo the last change undone, or the last N
+:help                      show this help
+:quit                      leave the simulator
+";
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ReplReply {
+    Output(String),
+    Quit,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ReplMission {
+    pub ur_plateau: Option<(i32, i32)>,
+    pub rovers_to_deploy: Vec<(PositionAndHeading, String)>,
+    // Lines setting up the mission, as written in a mission file, e.g. 'obstacle 2 2'
+    pub directives: Vec<String>,
+}
+
+// Everything entered so far, kept between runs so a mission can be corrected and run
+// again. Every change is recorded, so any of them can be undone
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Repl {
+    pub history: History<ReplMission>,
+}
+
+fn parse_coordinates(words: &[&str]) -> Option<(i32, i32)> {
+    // Missions are run as mission files, which only hold single digit coordinates
+    let coordinate = |word: &str| word.parse().ok().filter(|c| (0..=9).contains(c));
+    match words {
+        [x, y] => Some((coordinate(x)?, coordinate(y)?)),
+        _ => None,
+    }
+}
+
+impl Repl {
+    pub fn new() -> Repl {
+        Repl::default()
+    }
+
+    pub fn mission(&self) -> &ReplMission {
+        self.history.current()
+    }
+
+    fn change(&mut self, change: impl FnOnce(&mut ReplMission)) {
+        let mut mission = self.mission().clone();
+        change(&mut mission);
+        self.history.record(mission);
+    }
+
+    fn list(&self) -> String {
+        let mission = self.mission();
+        let mut listed = match mission.ur_plateau {
+            Some((x, y)) => format!("Plateau: {} {}\n", x, y),
+            None => "Plateau: not set\n".to_string(),
+        };
+        for directive in &mission.directives {
+            listed.push_str(&format!("Option: {}\n", directive));
+        }
+        if mission.rovers_to_deploy.is_empty() {
+            listed.push_str("No rovers\n");
+        }
+        for (index, (position, program)) in mission.rovers_to_deploy.iter().enumerate() {
+            listed.push_str(&format!(
+                "Rover {}: {} {} {} {}\n",
+                index + 1,
+                position.x,
+                position.y,
+                heading_name(position.heading),
+                program
+            ));
+        }
+        listed
+    }
+
+    fn add_rover(&mut self, words: &[&str]) -> String {
+        // Programs can hold spaces, e.g. 'if blocked { R } else { M }', so take the rest of the line
+        let start = match words {
+            [x, y, heading, ..] => parse_coordinates(&[x, y])
+                .zip(parse_heading(heading))
+                .map(|((x, y), heading)| PositionAndHeading { x, y, heading }),
+            _ => None,
+        };
+        match start {
+            Some(start) => {
+                let program = words[3..].join(" ");
+                self.change(|mission| mission.rovers_to_deploy.push((start, program)));
+                format!("Added rover {}\n", self.mission().rovers_to_deploy.len())
+            }
+            None => "Error: InvalidStartPosition. Please use ':rover X Y HEADING PROGRAM', with X and Y from 0 to 9.\n"
+                .to_string(),
+        }
+    }
+
+    fn add_option(&mut self, words: &[&str]) -> String {
+        // Directives are only read in full when the mission is run, as most need the plateau
+        let directive = words.join(" ");
+        if !is_directive(&directive) {
+            return "Error: InvalidOption. Please use ':option DIRECTIVE', e.g. ':option obstacle 2 2'.\n"
+                .to_string();
+        }
+        self.change(|mission| mission.directives.push(directive));
+        format!("Added option {}\n", self.mission().directives.len())
+    }
+
+    fn undo_redo(&mut self, count: &[&str], undo: bool) -> String {
+        let count = match count {
+            [] => 1,
+            [count] => match count.parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => return format!("Error: invalid count '{}'.\n", count),
+            },
+            _ => return "Error: please give at most one count.\n".to_string(),
+        };
+        // Stop early when there's nothing more to undo or redo
+        let mut changes = 0;
+        while changes < count {
+            let moved = if undo {
+                self.history.undo()
+            } else {
+                self.history.redo()
+            };
+            if moved.is_none() {
+                break;
+            }
+            changes += 1;
+        }
+        match (changes, undo) {
+            (0, true) => "Nothing to undo\n".to_string(),
+            (0, false) => "Nothing to redo\n".to_string(),
+            (changes, true) => format!("Undid {} change(s)\n", changes),
+            (changes, false) => format!("Redid {} change(s)\n", changes),
+        }
+    }
+
+    fn run(&self) -> String {
+        let ur_plateau = match self.mission().ur_plateau {
+            Some(ur_plateau) => ur_plateau,
+            None => return "Error: no plateau. Please set one with ':plateau X Y'.\n".to_string(),
+        };
+        if self.mission().rovers_to_deploy.is_empty() {
+            return "Error: no rovers. Please add one with ':rover X Y HEADING PROGRAM'.\n"
+                .to_string();
+        }
+        // The mission is run as the mission file it would be, with the options first
+        let mut lines = self.mission().directives.clone();
+        lines.push(format!("{} {}", ur_plateau.0, ur_plateau.1));
+        for (position, program) in &self.mission().rovers_to_deploy {
+            lines.push(format!(
+                "{} {} {}",
+                position.x,
+                position.y,
+                heading_name(position.heading)
+            ));
+            lines.push(program.clone());
+        }
+        match parse_mission(lines).and_then(|mission| rover_outcomes(&mission)) {
+            Ok(outcomes) => format!(
+                "Final rover position(s):\n\n{}",
+                TextFormatter.format(&outcomes)
+            ),
+            Err(err) => TextFormatter.format_error(&err),
+        }
+    }
+
+    pub fn handle_line(&mut self, line: &str) -> ReplReply {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let output = match words.as_slice() {
+            [] => String::new(),
+            [":plateau", coordinates @ ..] => match parse_coordinates(coordinates) {
+                Some((x, y)) => {
+                    self.change(|mission| mission.ur_plateau = Some((x, y)));
+                    format!("Plateau set to {} {}\n", x, y)
+                }
+                None => {
+                    "Error: InvalidPlateau. Please use ':plateau X Y', with X and Y from 0 to 9.\n"
+                        .to_string()
+                }
+            },
+            [":rover", rover @ ..] => self.add_rover(rover),
+            [":option", directive @ ..] => self.add_option(directive),
+            [":list"] => self.list(),
+            [":delete", rover] => match rover.parse::<usize>() {
+                Ok(rover) if rover >= 1 && rover <= self.mission().rovers_to_deploy.len() => {
+                    self.change(|mission| {
+                        mission.rovers_to_deploy.remove(rover - 1);
+                    });
+                    format!("Deleted rover {}\n", rover)
+                }
+                _ => format!("Error: no rover '{}'. Please check ':list'.\n", rover),
+            },
+            [":run"] => self.run(),
+            [":reset"] => {
+                self.change(|mission| *mission = ReplMission::default());
+                "Cleared the plateau, rovers and options\n".to_string()
+            }
+            [":undo", count @ ..] => self.undo_redo(count, true),
+            [":redo", count @ ..] => self.undo_redo(count, false),
+            [":help"] => REPL_HELP.to_string(),
+            [":quit"] => return ReplReply::Quit,
+            _ => format!(
+                "Error: unknown command '{}'. Type ':help' for the commands.\n",
+                line.trim()
+            ),
+        };
+        ReplReply::Output(output)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::check::error_name;
+use crate::map::render_map;
+use crate::rover::{PositionAndHeading, SimulationResult};
+
+// Clears the terminal and moves the cursor to the top left, so each frame is
+// drawn over the last
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+pub const DEFAULT_FRAME_DELAY: u64 = 300; // Milliseconds between frames
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReplayFrame {
+    pub rover: usize, // The rover moving, counting from 0 in the order rovers are deployed
+    pub step: usize,  // Turns and cells driven so far by the moving rover
+    pub steps: usize, // Turns and cells the moving rover makes in all
+    // Earlier rovers parked where they finished, then the moving rover so far
+    pub trajectories: Vec<Vec<PositionAndHeading>>,
+    pub error: Option<String>, // Set on the last frame when the moving rover was stopped
+}
+
+pub fn replay_frames(result: &SimulationResult) -> Vec<ReplayFrame> {
+    let mut moving = result
+        .reports
+        .iter()
+        .map(|report| (&report.trajectory, None))
+        .collect::<Vec<_>>();
+    if let Some(failure) = &result.failure {
+        moving.push((&failure.report.trajectory, Some(error_name(&failure.error))));
+    }
+
+    // A frame for every position each rover takes, one rover after another
+    let mut frames = Vec::new();
+    for (rover, (trajectory, error)) in moving.iter().enumerate() {
+        let parked = result.reports[..rover.min(result.reports.len())]
+            .iter()
+            .map(|report| report.trajectory.clone());
+        let mut trajectories = parked.collect::<Vec<_>>();
+        trajectories.push(Vec::new());
+
+        for (step, position) in trajectory.iter().enumerate() {
+            trajectories[rover].push(position.clone());
+            let is_last = step == trajectory.len() - 1;
+            frames.push(ReplayFrame {
+                rover,
+                step,
+                steps: trajectory.len() - 1,
+                trajectories: trajectories.clone(),
+                error: if is_last { error.clone() } else { None },
+            });
+        }
+    }
+    frames
+}
+
+pub fn format_frame(
+    ur_plateau: (i32, i32),
+    obstacles: &[(i32, i32)],
+    frame: &ReplayFrame,
+) -> String {
+    let mut formatted = format!(
+        "Rover {}, step {}/{}\n\n",
+        frame.rover + 1,
+        frame.step,
+        frame.steps
+    );
+    formatted.push_str(&render_map(
+        ur_plateau,
+        obstacles,
+        &frame.trajectories,
+        true,
+    ));
+    if let Some(error) = &frame.error {
+        formatted.push_str(&format!("\nRover {} stopped: {}\n", frame.rover + 1, error));
+    }
+    formatted
+}
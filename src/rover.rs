@@ -1,11 +1,14 @@
-#[derive(Debug)]
+use crate::command::{CommandRegistry, BUILT_IN_COMMANDS};
+use crate::program::{parse_program, program_commands, Condition, Instruction, MAX_STEPS};
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct InputCommand {
     pub ur_plateau: (i32, i32), // Upper right plateau coordinates
     // The String below is a list of commands/moves for the rover
     pub rovers_to_deploy: Vec<(PositionAndHeading, String)>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct PositionAndHeading {
     pub x: i32,
     pub y: i32,
@@ -23,15 +26,205 @@ pub enum RoverError {
     InvalidPlateau,
     InvalidNumberOfCommandsForRover,
     StartOutOfBounds,
+    ProgramTooLong,
+    InvalidMacro { name: String, line: usize },
+    RecursiveMacro { name: String, line: usize },
+    UndefinedMacro(String),
+    InvalidObstacle { line: usize },
+    InvalidOption { line: usize },
+    InvalidCommand(char),
+    StepLimitExceeded,
+    BatteryDepleted,
+    InvalidFootprint,
+    PayloadFull,
+    InvalidJson(String),
+    InvalidTopology, // Asked of a hex plateau, for something only modelled on square cells
+}
+
+// Commands turning a diagonal rover 45 degrees left (anticlockwise) and right (clockwise)
+pub const DEFAULT_DIAGONAL_TURNS: (char, char) = ('A', 'C');
+
+// Headings are a single char, so the diagonal ones are stored as arrows and
+// written with their two letter names, e.g. '1 2 NE'
+pub const NORTH_EAST: char = '↗';
+pub const SOUTH_EAST: char = '↘';
+pub const SOUTH_WEST: char = '↙';
+pub const NORTH_WEST: char = '↖';
+
+const DIAGONAL_HEADINGS: [(char, &str); 4] = [
+    (NORTH_EAST, "NE"),
+    (SOUTH_EAST, "SE"),
+    (SOUTH_WEST, "SW"),
+    (NORTH_WEST, "NW"),
+];
+
+// Headings clockwise from North, 45 degrees apart
+const COMPASS: [char; 8] = [
+    'N', NORTH_EAST, 'E', SOUTH_EAST, 'S', SOUTH_WEST, 'W', NORTH_WEST,
+];
+
+// Headings on a hex plateau clockwise from North East, 60 degrees apart
+const HEX_COMPASS: [char; 6] = [NORTH_EAST, 'E', SOUTH_EAST, SOUTH_WEST, 'W', NORTH_WEST];
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Topology {
+    #[default]
+    Square,
+    // Hexagonal cells in axial coordinates, with x running East and y running
+    // North East, so every cell has six neighbours
+    Hex,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoverProfile {
+    pub diagonal: bool, // Can also face NE, SE, SW and NW and drive diagonally
+    pub speed: usize,   // Cells driven by each move command
+    // The only commands the rover can run, or None if it can run them all
+    pub allowed_commands: Option<Vec<char>>,
+    // Turns and cells driven before the rover runs flat, or None if it never does
+    pub battery: Option<usize>,
+    // Cells across and front to back the rover covers, from its reported position
+    // in the rear left corner. Larger rovers need a square plateau and right angle turns
+    pub footprint: (usize, usize),
+    pub payload: Option<usize>, // Samples the rover can carry, or None if there's no limit
+}
+
+impl Default for RoverProfile {
+    fn default() -> Self {
+        RoverProfile {
+            diagonal: false,
+            speed: 1,
+            allowed_commands: None,
+            battery: None,
+            footprint: (1, 1),
+            payload: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MissionOptions {
+    pub obstacles: Vec<(i32, i32)>, // Cells no rover can drive into
+    // Profiles in the order rovers are deployed, rovers without one get the default
+    pub rover_profiles: Vec<RoverProfile>,
+    pub diagonal_turns: (char, char),
+    pub topology: Topology,
+    pub commands: CommandRegistry, // Custom commands on top of the built in ones
+    pub sample_sites: Vec<(i32, i32)>, // Cells with a sample for a rover to collect
+    // Rovers are stopped by a deployed rover anywhere on their path, rather than only
+    // colliding with one where they finish
+    pub path_collisions: bool,
+}
+
+impl Default for MissionOptions {
+    fn default() -> Self {
+        MissionOptions {
+            obstacles: Vec::new(),
+            rover_profiles: Vec::new(),
+            diagonal_turns: DEFAULT_DIAGONAL_TURNS,
+            topology: Topology::Square,
+            commands: CommandRegistry::new(),
+            sample_sites: Vec::new(),
+            path_collisions: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoverReport {
+    // The deployed position, followed by the position and heading after every
+    // turn and every cell the rover drove
+    pub trajectory: Vec<PositionAndHeading>,
+    // The command letter behind each step of the trajectory after the deployed
+    // position, so a fast rover's move shows up once for every cell it drove
+    pub commands: Vec<char>,
+    pub samples: Vec<(i32, i32)>, // Sample sites collected, in the order they were taken
+    pub battery_used: usize,      // Turns and cells driven, each using a unit of battery
+    // The cell the rover was stopped from driving into, off the plateau or onto an
+    // obstacle or another rover, if it was stopped on the way
+    pub blocked: Option<(i32, i32)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RoverFailure {
+    pub rover: usize, // Counting from 0 in the order rovers are deployed
+    pub error: RoverError,
+    // Everything the rover did before it was stopped, so its last position is
+    // where the mission ended
+    pub report: RoverReport,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SimulationResult {
+    pub reports: Vec<RoverReport>, // Rovers that finished their programs
+    pub failure: Option<RoverFailure>,
+}
+
+// What a rover can sense while its program runs
+struct Surroundings<'a> {
+    rover: usize, // The rover's place in the deployment order, counting from 0
+    profile: &'a RoverProfile,
+    ur_plateau: (i32, i32),
+    options: &'a MissionOptions,
+    deployed_cells: &'a [(i32, i32)], // Every cell covered by rovers already deployed
+    rovers_block: bool,               // Whether deployed rovers stop the rover driving into them
+    collected_samples: &'a [(i32, i32)], // Sample sites emptied by rovers already deployed
+}
+
+pub fn heading_name(heading: char) -> String {
+    match DIAGONAL_HEADINGS.iter().find(|(c, _)| *c == heading) {
+        Some((_, name)) => name.to_string(),
+        None => heading.to_string(),
+    }
+}
+
+pub fn parse_heading(name: &str) -> Option<char> {
+    // Single letters are kept as they are, so an unknown one is reported as an
+    // invalid heading when the rover is deployed
+    let name = name.to_uppercase();
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(heading), None) => Some(heading),
+        _ => DIAGONAL_HEADINGS
+            .iter()
+            .find(|(_, diagonal_name)| *diagonal_name == name)
+            .map(|(heading, _)| *heading),
+    }
 }
 
 pub fn print_final_rover_positions(positions: Vec<PositionAndHeading>) {
     println!("\nFinal rover position(s):\n");
     for position in positions {
-        println!("{} {} {}", position.x, position.y, position.heading);
+        println!(
+            "{} {} {}",
+            position.x,
+            position.y,
+            heading_name(position.heading)
+        );
     }
 }
 
+pub fn format_collected_samples(reports: &[RoverReport]) -> String {
+    let mut formatted = String::from("\nCollected sample(s):\n\n");
+    for (index, report) in reports.iter().enumerate() {
+        let samples = report
+            .samples
+            .iter()
+            .map(|(x, y)| format!("{} {}", x, y))
+            .collect::<Vec<_>>();
+        if samples.is_empty() {
+            formatted.push_str(&format!("Rover {}: none\n", index + 1));
+        } else {
+            formatted.push_str(&format!("Rover {}: {}\n", index + 1, samples.join(", ")));
+        }
+    }
+    formatted
+}
+
+pub fn print_collected_samples(reports: &[RoverReport]) {
+    print!("{}", format_collected_samples(reports));
+}
+
 pub fn parse_user_plateau(plateau: String) -> Result<(i32, i32), RoverError> {
     // Help the user by stripping out non digit chars
     let plateau = plateau
@@ -82,8 +275,14 @@ pub fn parse_rover_to_deploy(
 }
 
 fn parse_rover_commands(commands: Vec<char>) -> Result<PositionAndHeading, RoverError> {
-    // Rover position must be 3 chars: x, y coordinate and a heading
-    if commands.len() == 3 {
+    // Rover position must be 3 chars: x, y coordinate and a heading, or 4 chars
+    // when the heading is a diagonal one such as 'NE'
+    let heading = match commands.len() {
+        3 => Some(commands[2]),
+        4 => parse_heading(&commands[2..].iter().collect::<String>()),
+        _ => None,
+    };
+    if let Some(heading) = heading {
         let output = PositionAndHeading {
             x: match commands[0].to_digit(10) {
                 Some(x) => x as i32,
@@ -93,7 +292,7 @@ fn parse_rover_commands(commands: Vec<char>) -> Result<PositionAndHeading, Rover
                 Some(x) => x as i32,
                 None => return Err(RoverError::InvalidStartMove),
             },
-            heading: commands[2],
+            heading,
         };
         Ok(output)
     } else {
@@ -101,147 +300,615 @@ fn parse_rover_commands(commands: Vec<char>) -> Result<PositionAndHeading, Rover
     }
 }
 
-fn is_valid_heading(heading: char) -> bool {
-    matches!(heading, 'N' | 'E' | 'S' | 'W')
+fn is_valid_heading(heading: char, profile: &RoverProfile, topology: Topology) -> bool {
+    match topology {
+        Topology::Hex => HEX_COMPASS.contains(&heading),
+        Topology::Square if profile.diagonal => COMPASS.contains(&heading),
+        Topology::Square => matches!(heading, 'N' | 'E' | 'S' | 'W'),
+    }
 }
 
-fn is_valid_move(move_to_check: &str) -> bool {
-    move_to_check
-        .chars()
-        .find(|x| !matches!(x, 'L' | 'R' | 'M'))
-        .is_none()
+fn is_valid_move(move_to_check: &str, profile: &RoverProfile, options: &MissionOptions) -> bool {
+    // Every hex heading already faces a neighbour, so diagonal turns only apply to squares
+    let can_turn_diagonally = profile.diagonal && options.topology == Topology::Square;
+    let is_allowed = |x: char| match &profile.allowed_commands {
+        Some(allowed_commands) => allowed_commands.contains(&x),
+        None => true,
+    };
+    move_to_check.chars().all(|x| {
+        is_allowed(x)
+            && (BUILT_IN_COMMANDS.contains(&x)
+                || options.commands.get(x).is_some()
+                || (can_turn_diagonally
+                    && (x == options.diagonal_turns.0 || x == options.diagonal_turns.1)))
+    })
 }
 
-fn parse_input_commands(commands: InputCommand) -> Result<InputCommand, RoverError> {
-    let mut fixed_input_command = InputCommand {
-        ur_plateau: commands.ur_plateau,
-        rovers_to_deploy: Vec::new(),
+fn get_profile(options: &MissionOptions, rover: usize) -> RoverProfile {
+    options
+        .rover_profiles
+        .get(rover)
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn uses_battery(command: char, options: &MissionOptions, profile: &RoverProfile) -> bool {
+    // Each turn or cell driven uses a unit of the rover's battery, while samples
+    // and custom commands don't
+    command != 'S' && get_rotation(command, options, profile).is_some()
+}
+
+pub fn get_step(
+    position_and_heading: &PositionAndHeading,
+    command: char,
+    rover: usize,
+    options: &MissionOptions,
+) -> Option<PositionAndHeading> {
+    // Where a single step of a built in command takes the rover deployed at `rover`,
+    // ignoring the plateau's edges, obstacles and other rovers. Moves drive a single
+    // cell however fast the rover is, and None means the rover has no such command
+    let profile = get_profile(options, rover);
+    if !is_valid_move(&command.to_string(), &profile, options) {
+        return None;
+    }
+    let rotation = get_rotation(command, options, &profile)?;
+    let mut next_position_and_heading = position_and_heading.clone();
+    next_position_and_heading.heading =
+        get_next_heading((position_and_heading.heading, rotation), options.topology)?;
+    let next_position = match command {
+        'M' => get_next_position(&next_position_and_heading, options.topology),
+        'B' => get_previous_position(&next_position_and_heading, options.topology),
+        _ => (position_and_heading.x, position_and_heading.y),
     };
+    next_position_and_heading.x = next_position.0;
+    next_position_and_heading.y = next_position.1;
+    Some(next_position_and_heading)
+        .filter(|next| is_valid_heading(next.heading, &profile, options.topology))
+}
+
+fn parse_input_commands(
+    commands: InputCommand,
+    options: &MissionOptions,
+) -> Result<Vec<(PositionAndHeading, Vec<Instruction>)>, RoverError> {
+    let mut parsed_rovers = Vec::new();
+    let ur_plateau = commands.ur_plateau;
 
-    for command in commands.rovers_to_deploy {
+    for (index, command) in commands.rovers_to_deploy.into_iter().enumerate() {
         let mut command = command;
+        let profile = get_profile(options, index);
         // Help the user by forcing the heading to uppercase
-        command.0.heading = command
-            .0
-            .heading
-            .to_uppercase()
-            .to_string()
-            .chars()
-            .next()
-            .unwrap();
-        // Help the user by forcing the commands to uppercase and removing non-alphabetic chars
-        command.1 = command
-            .1
-            .to_uppercase()
-            .chars()
-            .filter(|c| c.is_alphabetic())
-            .collect::<String>();
-
-        if command.0.x > commands.ur_plateau.0 || command.0.y > commands.ur_plateau.1 {
+        command.0.heading = command.0.heading.to_ascii_uppercase();
+        // Help the user by forcing the commands to uppercase, then read any counts,
+        // repeated groups, conditions and loops into instructions
+        let program = parse_program(&command.1.to_uppercase())?;
+
+        if command.0.x > ur_plateau.0 || command.0.y > ur_plateau.1 {
             return Err(RoverError::StartOutOfBounds);
         }
-        if !is_valid_heading(command.0.heading) {
+        let is_large = profile.footprint != (1, 1);
+        if is_large
+            && (profile.footprint.0 == 0
+                || profile.footprint.1 == 0
+                || profile.diagonal
+                || options.topology == Topology::Hex)
+        {
+            return Err(RoverError::InvalidFootprint);
+        }
+        if !is_valid_heading(command.0.heading, &profile, options.topology) {
             return Err(RoverError::InvalidHeading);
         }
-        if !is_valid_move(&command.1) {
+        if get_footprint_cells(&command.0, profile.footprint)
+            .iter()
+            .any(|cell| !is_on_plateau(*cell, ur_plateau))
+        {
+            return Err(RoverError::StartOutOfBounds);
+        }
+        if !is_valid_move(&program_commands(&program), &profile, options) {
             return Err(RoverError::InvalidMove);
         }
-        fixed_input_command.rovers_to_deploy.push(command);
+        parsed_rovers.push((command.0, program));
+    }
+    Ok(parsed_rovers)
+}
+
+fn get_compass(topology: Topology) -> &'static [char] {
+    match topology {
+        Topology::Square => &COMPASS,
+        Topology::Hex => &HEX_COMPASS,
     }
-    Ok(fixed_input_command)
 }
 
-fn get_next_heading(heading_and_rotation: (char, char)) -> Option<char> {
-    // Given current heading and intended next move, map the next heading
-    match heading_and_rotation {
-        ('N', 'L') => Some('W'),
-        ('N', 'R') => Some('E'),
-        ('E', 'L') => Some('N'),
-        ('E', 'R') => Some('S'),
-        ('S', 'L') => Some('E'),
-        ('S', 'R') => Some('W'),
-        ('W', 'L') => Some('S'),
-        ('W', 'R') => Some('N'),
-        (heading, 'M') => Some(heading), // Moves don't mutate headings
+fn get_rotation(command: char, options: &MissionOptions, profile: &RoverProfile) -> Option<i32> {
+    // How far a command turns the rover, in clockwise steps around the compass:
+    // 45 degrees on a square plateau and 60 degrees on a hex one. Only diagonal
+    // rovers turn with the diagonal turn letters, so other rovers can use them
+    // as custom commands
+    match (options.topology, command) {
+        (_, 'M') | (_, 'B') | (_, 'S') => Some(0), // Moves and samples don't mutate headings
+        (topology, 'U') => Some(get_compass(topology).len() as i32 / 2),
+        (Topology::Square, 'L') => Some(-2),
+        (Topology::Square, 'R') => Some(2),
+        (Topology::Square, c) if profile.diagonal && c == options.diagonal_turns.0 => Some(-1),
+        (Topology::Square, c) if profile.diagonal && c == options.diagonal_turns.1 => Some(1),
+        (Topology::Hex, 'L') => Some(-1),
+        (Topology::Hex, 'R') => Some(1),
         (_, _) => None,
     }
 }
 
-fn will_not_collide(
-    new_rover_position: &PositionAndHeading,
-    current_rover_positions: &[PositionAndHeading],
-) -> bool {
-    // Check that for all rover positions, is the next rover in the same position
-    current_rover_positions
+fn get_next_heading(heading_and_rotation: (char, i32), topology: Topology) -> Option<char> {
+    // Given current heading and how far the rover turns, map the next heading
+    let (heading, rotation) = heading_and_rotation;
+    let compass = get_compass(topology);
+    let index = compass.iter().position(|h| *h == heading)? as i32;
+    Some(compass[(index + rotation).rem_euclid(compass.len() as i32) as usize])
+}
+
+fn will_not_collide(new_rover_cells: &[(i32, i32)], current_rover_cells: &[(i32, i32)]) -> bool {
+    // Check that none of the cells the next rover covers is covered by another rover
+    new_rover_cells
         .iter()
-        .find(|current_rover_position| {
-            current_rover_position.x == new_rover_position.x
-                && current_rover_position.y == new_rover_position.y
-        })
-        .is_none()
+        .all(|cell| !current_rover_cells.contains(cell))
 }
 
-pub fn simulate_rover_move(
-    input_command: InputCommand,
-) -> Result<Vec<PositionAndHeading>, RoverError> {
-    let mut output = Vec::new();
+fn get_footprint_cells(
+    position_and_heading: &PositionAndHeading,
+    footprint: (usize, usize),
+) -> Vec<(i32, i32)> {
+    let (x, y) = (position_and_heading.x, position_and_heading.y);
+    if footprint == (1, 1) {
+        return vec![(x, y)];
+    }
+    // Rotate the footprint with the rover, spreading right and forward from its corner
+    let forward = get_square_next_position((0, 0), position_and_heading.heading);
+    let right = (forward.1, -forward.0);
+    let mut cells = Vec::new();
+    for across in 0..footprint.0 as i32 {
+        for ahead in 0..footprint.1 as i32 {
+            cells.push((
+                x + across * right.0 + ahead * forward.0,
+                y + across * right.1 + ahead * forward.1,
+            ));
+        }
+    }
+    cells
+}
+
+fn get_next_position(position_and_heading: &PositionAndHeading, topology: Topology) -> (i32, i32) {
+    // The cell directly in front of the rover, North being (x, y+1)
+    let (x, y) = (position_and_heading.x, position_and_heading.y);
+    match (topology, position_and_heading.heading) {
+        (Topology::Hex, NORTH_EAST) => (x, y + 1),
+        (Topology::Hex, SOUTH_EAST) => (x + 1, y - 1),
+        (Topology::Hex, SOUTH_WEST) => (x, y - 1),
+        (Topology::Hex, NORTH_WEST) => (x - 1, y + 1),
+        // East and West neighbours are the same on either plateau
+        (_, heading) => get_square_next_position((x, y), heading),
+    }
+}
+
+fn get_square_next_position(position: (i32, i32), heading: char) -> (i32, i32) {
+    let (x, y) = position;
+    match heading {
+        'N' => (x, y + 1),
+        NORTH_EAST => (x + 1, y + 1),
+        'E' => (x + 1, y),
+        SOUTH_EAST => (x + 1, y - 1),
+        'S' => (x, y - 1),
+        SOUTH_WEST => (x - 1, y - 1),
+        'W' => (x - 1, y),
+        NORTH_WEST => (x - 1, y + 1),
+        _ => (x, y),
+    }
+}
+
+fn get_previous_position(
+    position_and_heading: &PositionAndHeading,
+    topology: Topology,
+) -> (i32, i32) {
+    // The cell directly behind the rover, opposite the one in front of it
+    let (x, y) = (position_and_heading.x, position_and_heading.y);
+    let next_position = get_next_position(position_and_heading, topology);
+    (2 * x - next_position.0, 2 * y - next_position.1)
+}
+
+fn is_on_plateau(cell: (i32, i32), ur_plateau: (i32, i32)) -> bool {
     let lr_plateau = (0, 0); // Lower right plateau coordinates
-    let input_command = parse_input_commands(input_command)?;
-
-    for rovers_to_deploy in input_command.rovers_to_deploy {
-        // Keep the current rover state in memory to mutate as moves are processed
-        let mut current_position_and_heading = rovers_to_deploy.0;
-
-        for command in rovers_to_deploy.1.chars() {
-            let next_heading = get_next_heading((current_position_and_heading.heading, command));
-
-            if let Some(next_heading) = next_heading {
-                current_position_and_heading.heading = next_heading;
-                // Apply moves if move command found
-                if command == 'M' {
-                    // Move relevant coordinate depending on heading and checking
-                    // if the next move is within the plateau bounds
-                    match next_heading {
-                        'N' => {
-                            if current_position_and_heading.y < input_command.ur_plateau.1 {
-                                current_position_and_heading.y += 1
-                            } else {
-                                return Err(RoverError::OutOfBounds);
-                            }
-                        }
-                        'E' => {
-                            if current_position_and_heading.x < input_command.ur_plateau.0 {
-                                current_position_and_heading.x += 1
-                            } else {
-                                return Err(RoverError::OutOfBounds);
-                            }
-                        }
-                        'S' => {
-                            if current_position_and_heading.y > lr_plateau.1 {
-                                current_position_and_heading.y -= 1
-                            } else {
-                                return Err(RoverError::OutOfBounds);
-                            }
-                        }
-                        'W' => {
-                            if current_position_and_heading.x > lr_plateau.0 {
-                                current_position_and_heading.x -= 1
-                            } else {
-                                return Err(RoverError::OutOfBounds);
+    cell.0 >= lr_plateau.0
+        && cell.1 >= lr_plateau.1
+        && cell.0 <= ur_plateau.0
+        && cell.1 <= ur_plateau.1
+}
+
+fn is_occupied(cell: (i32, i32), surroundings: &Surroundings) -> bool {
+    surroundings.options.obstacles.contains(&cell)
+        || (surroundings.rovers_block && surroundings.deployed_cells.contains(&cell))
+}
+
+fn is_corner_blocked(
+    position_and_heading: &PositionAndHeading,
+    next_position: (i32, i32),
+    surroundings: &Surroundings,
+) -> bool {
+    // A diagonal move squeezes between the two cells either side of the corner,
+    // so it can't cut past an obstacle or rover in either of them. Hex cells
+    // share an edge with every neighbour so have no corners to cut
+    let (x, y) = (position_and_heading.x, position_and_heading.y);
+    surroundings.options.topology == Topology::Square
+        && x != next_position.0
+        && y != next_position.1
+        && (is_occupied((next_position.0, y), surroundings)
+            || is_occupied((x, next_position.1), surroundings))
+}
+
+fn check_condition(
+    condition: &Condition,
+    position_and_heading: &PositionAndHeading,
+    surroundings: &Surroundings,
+) -> bool {
+    // Conditions look one cell ahead, where every cell the rover covers would move to
+    match condition {
+        Condition::Edge => {
+            let cell_ahead = get_next_position(position_and_heading, surroundings.options.topology);
+            let position_ahead = PositionAndHeading {
+                x: cell_ahead.0,
+                y: cell_ahead.1,
+                ..position_and_heading.clone()
+            };
+            get_footprint_cells(&position_ahead, surroundings.profile.footprint)
+                .iter()
+                .any(|cell| !is_on_plateau(*cell, surroundings.ur_plateau))
+        }
+        Condition::Blocked => {
+            // Rovers can always see a deployed rover ahead, even when they could drive through it
+            let sensing = Surroundings {
+                rovers_block: true,
+                ..*surroundings
+            };
+            move_rover(position_and_heading, 'M', &sensing).is_err()
+        }
+        Condition::Not(condition) => {
+            !check_condition(condition, position_and_heading, surroundings)
+        }
+    }
+}
+
+fn check_footprint(
+    position_and_heading: &PositionAndHeading,
+    surroundings: &Surroundings,
+) -> Result<(), RoverError> {
+    let cells = get_footprint_cells(position_and_heading, surroundings.profile.footprint);
+    if cells
+        .iter()
+        .any(|cell| !is_on_plateau(*cell, surroundings.ur_plateau))
+    {
+        Err(RoverError::OutOfBounds)
+    } else if cells.iter().any(|cell| is_occupied(*cell, surroundings)) {
+        Err(RoverError::Collision)
+    } else {
+        Ok(())
+    }
+}
+
+fn run_custom_command(
+    position_and_heading: &PositionAndHeading,
+    command: char,
+    surroundings: &Surroundings,
+) -> Option<Result<PositionAndHeading, RoverError>> {
+    let custom_command = surroundings.options.commands.get(command)?;
+    let next_position_and_heading =
+        match custom_command.apply(surroundings.rover, position_and_heading) {
+            Ok(next_position_and_heading) => next_position_and_heading,
+            Err(err) => return Some(Err(err)),
+        };
+
+    // Custom commands can put the rover anywhere, so hold them to the same rules as moves
+    let next_position = (next_position_and_heading.x, next_position_and_heading.y);
+    let is_moved = next_position != (position_and_heading.x, position_and_heading.y);
+    let is_large = surroundings.profile.footprint != (1, 1);
+    if !is_valid_heading(
+        next_position_and_heading.heading,
+        surroundings.profile,
+        surroundings.options.topology,
+    ) {
+        return Some(Err(RoverError::InvalidHeading));
+    }
+    if is_moved || (is_large && next_position_and_heading != *position_and_heading) {
+        if let Err(err) = check_footprint(&next_position_and_heading, surroundings) {
+            return Some(Err(err));
+        }
+    }
+    Some(Ok(next_position_and_heading))
+}
+
+fn move_rover(
+    position_and_heading: &PositionAndHeading,
+    command: char,
+    surroundings: &Surroundings,
+) -> Result<PositionAndHeading, RoverError> {
+    let mut next_position_and_heading = position_and_heading.clone();
+
+    // Built in commands come first, so diagonal turns win over custom commands
+    let rotation = get_rotation(command, surroundings.options, surroundings.profile);
+    if rotation.is_none() {
+        if let Some(result) = run_custom_command(position_and_heading, command, surroundings) {
+            return result;
+        }
+    }
+
+    if let Some(next_heading) = rotation.and_then(|rotation| {
+        get_next_heading(
+            (position_and_heading.heading, rotation),
+            surroundings.options.topology,
+        )
+    }) {
+        next_position_and_heading.heading = next_heading;
+        // Apply moves forward or backward if move command found, checking the next
+        // move is within the plateau bounds and clear of obstacles and deployed rovers
+        if command == 'M' || command == 'B' {
+            let next_position = if command == 'M' {
+                get_next_position(&next_position_and_heading, surroundings.options.topology)
+            } else {
+                get_previous_position(&next_position_and_heading, surroundings.options.topology)
+            };
+            next_position_and_heading.x = next_position.0;
+            next_position_and_heading.y = next_position.1;
+            check_footprint(&next_position_and_heading, surroundings)?;
+            if is_corner_blocked(position_and_heading, next_position, surroundings) {
+                return Err(RoverError::Collision);
+            }
+        } else if surroundings.profile.footprint != (1, 1) {
+            // Larger rovers swing their footprint round as they turn
+            check_footprint(&next_position_and_heading, surroundings)?;
+        }
+    }
+    Ok(next_position_and_heading)
+}
+
+fn count_step(steps: &mut usize) -> Result<(), RoverError> {
+    *steps += 1;
+    if *steps > MAX_STEPS {
+        Err(RoverError::StepLimitExceeded)
+    } else {
+        Ok(())
+    }
+}
+
+fn collect_sample(report: &mut RoverReport, surroundings: &Surroundings) -> Result<(), RoverError> {
+    let position_and_heading = &report.trajectory[report.trajectory.len() - 1];
+    let cell = (position_and_heading.x, position_and_heading.y);
+
+    // Each site holds one sample, so sampling anywhere else collects nothing
+    if !surroundings.options.sample_sites.contains(&cell)
+        || surroundings.collected_samples.contains(&cell)
+        || report.samples.contains(&cell)
+    {
+        return Ok(());
+    }
+    if let Some(payload) = surroundings.profile.payload {
+        if report.samples.len() >= payload {
+            return Err(RoverError::PayloadFull);
+        }
+    }
+    report.samples.push(cell);
+    Ok(())
+}
+
+fn get_blocked_cell(
+    position_and_heading: &PositionAndHeading,
+    command: char,
+    err: &RoverError,
+    topology: Topology,
+) -> Option<(i32, i32)> {
+    // Only moves head for a known cell, custom commands and turns stop the rover
+    // where it already is
+    match (command, err) {
+        ('M', RoverError::OutOfBounds | RoverError::Collision) => {
+            Some(get_next_position(position_and_heading, topology))
+        }
+        ('B', RoverError::OutOfBounds | RoverError::Collision) => {
+            Some(get_previous_position(position_and_heading, topology))
+        }
+        _ => None,
+    }
+}
+
+fn run_instructions(
+    instructions: &[Instruction],
+    surroundings: &Surroundings,
+    report: &mut RoverReport,
+    steps: &mut usize,
+) -> Result<(), RoverError> {
+    // The rover's current position is always the last step of its trajectory
+    for instruction in instructions {
+        match instruction {
+            Instruction::Command(command) => {
+                count_step(steps)?;
+                // Faster rovers drive a cell at a time, so every cell on the way is checked
+                let cells = if matches!(command, 'M' | 'B') {
+                    surroundings.profile.speed
+                } else {
+                    1
+                };
+                let uses_battery =
+                    uses_battery(*command, surroundings.options, surroundings.profile);
+                for _ in 0..cells {
+                    if uses_battery {
+                        if let Some(battery) = surroundings.profile.battery {
+                            if report.battery_used >= battery {
+                                return Err(RoverError::BatteryDepleted);
                             }
                         }
-                        _ => {}
+                        report.battery_used += 1;
                     }
+                    let current_position_and_heading =
+                        &report.trajectory[report.trajectory.len() - 1];
+                    let next_position_and_heading =
+                        match move_rover(current_position_and_heading, *command, surroundings) {
+                            Ok(next_position_and_heading) => next_position_and_heading,
+                            Err(err) => {
+                                report.blocked = get_blocked_cell(
+                                    current_position_and_heading,
+                                    *command,
+                                    &err,
+                                    surroundings.options.topology,
+                                );
+                                return Err(err);
+                            }
+                        };
+                    report.trajectory.push(next_position_and_heading);
+                    report.commands.push(*command);
+                }
+                if *command == 'S' {
+                    collect_sample(report, surroundings)?;
+                }
+            }
+            Instruction::Repeat(count, body) => {
+                for _ in 0..*count {
+                    count_step(steps)?;
+                    run_instructions(body, surroundings, report, steps)?;
+                }
+            }
+            Instruction::If(condition, then_block, else_block) => {
+                let current_position_and_heading = &report.trajectory[report.trajectory.len() - 1];
+                if check_condition(condition, current_position_and_heading, surroundings) {
+                    run_instructions(then_block, surroundings, report, steps)?;
+                } else {
+                    run_instructions(else_block, surroundings, report, steps)?;
                 }
             }
+            Instruction::While(condition, body) => {
+                while check_condition(
+                    condition,
+                    &report.trajectory[report.trajectory.len() - 1],
+                    surroundings,
+                ) {
+                    count_step(steps)?;
+                    run_instructions(body, surroundings, report, steps)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn simulate_rovers_until_failure(
+    input_command: InputCommand,
+    options: &MissionOptions,
+) -> Result<SimulationResult, RoverError> {
+    let mut reports: Vec<RoverReport> = Vec::new();
+    let mut deployed_cells: Vec<(i32, i32)> = Vec::new();
+    let mut collected_samples: Vec<(i32, i32)> = Vec::new();
+    let ur_plateau = input_command.ur_plateau;
+
+    // Invalid input is an error, while a rover failing during the mission is part of the result
+    let parsed_rovers = parse_input_commands(input_command, options)?;
+    for (index, (start_position_and_heading, program)) in parsed_rovers.into_iter().enumerate() {
+        let profile = get_profile(options, index);
+        let mut report = RoverReport {
+            trajectory: vec![start_position_and_heading],
+            commands: Vec::new(),
+            samples: Vec::new(),
+            battery_used: 0,
+            blocked: None,
+        };
+        let failure = |error, report| {
+            Ok(SimulationResult {
+                reports: reports.clone(),
+                failure: Some(RoverFailure {
+                    rover: index,
+                    error,
+                    report,
+                }),
+            })
+        };
+
+        if get_footprint_cells(&report.trajectory[0], profile.footprint)
+            .iter()
+            .any(|cell| options.obstacles.contains(cell))
+        {
+            let start = &report.trajectory[0];
+            report.blocked = Some((start.x, start.y));
+            return failure(RoverError::Collision, report);
+        }
+
+        // Rovers move one at a time, so earlier rovers are parked where they finished
+        let surroundings = Surroundings {
+            rover: index,
+            profile: &profile,
+            ur_plateau,
+            options,
+            deployed_cells: &deployed_cells,
+            rovers_block: options.path_collisions,
+            collected_samples: &collected_samples,
+        };
+        if let Err(error) = run_instructions(&program, &surroundings, &mut report, &mut 0) {
+            return failure(error, report);
         }
+
+        let final_position_and_heading = &report.trajectory[report.trajectory.len() - 1];
+        let final_cells = get_footprint_cells(final_position_and_heading, profile.footprint);
         // With all the moves applied, check if the rover won't collide to existing rovers
-        if will_not_collide(&current_position_and_heading, &output) {
-            output.push(current_position_and_heading);
+        if will_not_collide(&final_cells, &deployed_cells) {
+            deployed_cells.extend(final_cells);
+            collected_samples.extend(report.samples.iter().cloned());
+            reports.push(report);
         } else {
-            return Err(RoverError::Collision);
+            report.blocked = Some((final_position_and_heading.x, final_position_and_heading.y));
+            return failure(RoverError::Collision, report);
         }
     }
 
+    Ok(SimulationResult {
+        reports,
+        failure: None,
+    })
+}
+
+pub fn simulate_rovers_with_options(
+    input_command: InputCommand,
+    options: &MissionOptions,
+) -> Result<Vec<RoverReport>, RoverError> {
+    let result = simulate_rovers_until_failure(input_command, options)?;
+    match result.failure {
+        Some(failure) => Err(failure.error),
+        None => Ok(result.reports),
+    }
+}
+
+pub fn trace_rover_moves_with_options(
+    input_command: InputCommand,
+    options: &MissionOptions,
+) -> Result<Vec<Vec<PositionAndHeading>>, RoverError> {
+    let trajectories = simulate_rovers_with_options(input_command, options)?
+        .into_iter()
+        .map(|report| report.trajectory)
+        .collect();
+
+    Ok(trajectories)
+}
+
+pub fn trace_rover_moves(
+    input_command: InputCommand,
+) -> Result<Vec<Vec<PositionAndHeading>>, RoverError> {
+    trace_rover_moves_with_options(input_command, &MissionOptions::default())
+}
+
+pub fn simulate_rover_move_with_options(
+    input_command: InputCommand,
+    options: &MissionOptions,
+) -> Result<Vec<PositionAndHeading>, RoverError> {
+    // The final position of each rover is the last step of its trajectory
+    let output = trace_rover_moves_with_options(input_command, options)?
+        .into_iter()
+        .filter_map(|mut trajectory| trajectory.pop())
+        .collect();
+
     Ok(output)
 }
+
+pub fn simulate_rover_move(
+    input_command: InputCommand,
+) -> Result<Vec<PositionAndHeading>, RoverError> {
+    simulate_rover_move_with_options(input_command, &MissionOptions::default())
+}
@@ -0,0 +1,209 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::check::{error_name, rover_outcomes};
+use crate::json::{format_json, JsonValue};
+use crate::mission::{parse_json_mission, parse_mission};
+use crate::output::{error_detail, error_json, outcomes_json};
+use crate::rover::RoverError;
+
+pub const DEFAULT_PORT: u16 = 8080;
+
+// Requests bigger than this are refused rather than read into memory
+const MAX_BODY_LENGTH: usize = 1_000_000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String, // Always JSON
+}
+
+fn error_response(status: u16, name: &str, detail: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: format_json(&error_json(name, detail)),
+    }
+}
+
+fn mission_error(err: &RoverError) -> HttpResponse {
+    error_response(400, &error_name(err), &error_detail(err))
+}
+
+fn simulate(request: &HttpRequest) -> HttpResponse {
+    // Missions are JSON when sent as JSON or when they look like it, and text otherwise
+    let is_json = request
+        .content_type
+        .as_ref()
+        .is_some_and(|content_type| content_type.contains("json"))
+        || request.body.trim_start().starts_with('{');
+    let parsed_mission = if is_json {
+        parse_json_mission(&request.body)
+    } else {
+        parse_mission(
+            request
+                .body
+                .lines()
+                .map(|line| line.trim().to_string())
+                .collect(),
+        )
+    };
+
+    // A rover failing is part of the result, only missions that can't be run are errors
+    match parsed_mission.and_then(|mission| rover_outcomes(&mission)) {
+        Ok(outcomes) => HttpResponse {
+            status: 200,
+            body: format_json(&JsonValue::Object(vec![(
+                "rovers".to_string(),
+                outcomes_json(&outcomes),
+            )])),
+        },
+        Err(err) => mission_error(&err),
+    }
+}
+
+pub fn handle_request(request: &HttpRequest) -> HttpResponse {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => HttpResponse {
+            status: 200,
+            body: "{\"status\":\"ok\"}".to_string(),
+        },
+        ("POST", "/simulate") => simulate(request),
+        (_, "/health") | (_, "/simulate") => error_response(
+            405,
+            "MethodNotAllowed",
+            &format!("{} isn't allowed on {}", request.method, request.path),
+        ),
+        _ => error_response(
+            404,
+            "NotFound",
+            &format!("there's nothing at {}", request.path),
+        ),
+    }
+}
+
+pub fn read_request<R: BufRead>(reader: &mut R) -> Result<HttpRequest, HttpResponse> {
+    let bad_request = |detail: &str| error_response(400, "BadRequest", detail);
+    // Lines that end early, whether the client stopped or timed out, are missing
+    let mut read_line = |missing: &str| {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => Err(bad_request(missing)),
+            Ok(_) => Ok(line.trim_end().to_string()),
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                Err(bad_request("the request isn't UTF-8"))
+            }
+            Err(_) => Err(bad_request(missing)),
+        }
+    };
+
+    // The request line, e.g. 'POST /simulate HTTP/1.1', then headers up to a blank line
+    let request_line = read_line("the request is empty")?;
+    let (method, path) = match request_line
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [method, path, _] => (method.to_string(), path.to_string()),
+        _ => return Err(bad_request("the request line is invalid")),
+    };
+    let mut content_length = 0;
+    let mut content_type = None;
+    let mut is_chunked = false;
+    loop {
+        let header = read_line("the headers are incomplete")?;
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_lowercase().as_str() {
+                "content-length" => {
+                    content_length = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| bad_request("the content length is invalid"))?;
+                }
+                "content-type" => content_type = Some(value.trim().to_lowercase()),
+                "transfer-encoding" => is_chunked = true,
+                _ => {}
+            }
+        }
+    }
+
+    // Bodies are only read by their length, so chunked ones would be read as empty
+    if is_chunked {
+        return Err(error_response(
+            411,
+            "LengthRequired",
+            "missions must be sent with a Content-Length rather than a Transfer-Encoding",
+        ));
+    }
+    if content_length > MAX_BODY_LENGTH {
+        return Err(error_response(
+            413,
+            "PayloadTooLarge",
+            &format!("missions can be at most {} bytes", MAX_BODY_LENGTH),
+        ));
+    }
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad_request("the body is shorter than its content length"))?;
+    let body = String::from_utf8(body).map_err(|_| bad_request("the body isn't UTF-8"))?;
+
+    Ok(HttpRequest {
+        method,
+        path,
+        content_type,
+        body,
+    })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    // A client that stops sending can't hold up the requests after it for long
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let response = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => handle_request(&request),
+        Err(response) => response,
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason_phrase(response.status),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+pub fn serve(port: u16) -> io::Result<()> {
+    // Only reachable from this machine. Requests are answered one at a time
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    // A connection going wrong only affects that request
+    for stream in listener.incoming().flatten() {
+        let _ = handle_connection(stream);
+    }
+    Ok(())
+}
@@ -0,0 +1,530 @@
+use crate::check::error_name;
+use crate::command::CommandRegistry;
+use crate::json::{format_json, parse_json, JsonValue};
+use crate::mission::{
+    format_capabilities, invalid_json, json_options, json_rover_type, parse_capability,
+    parse_options, rover_type_json,
+};
+use crate::program::expand_program;
+use crate::rover::{
+    heading_name, parse_heading, simulate_rovers_until_failure, uses_battery, InputCommand,
+    MissionOptions, PositionAndHeading, RoverError, RoverProfile, RoverReport, SimulationResult,
+    Topology, DEFAULT_DIAGONAL_TURNS,
+};
+
+// Mission directives a snapshot can hold, written the same as in mission files
+const SNAPSHOT_DIRECTIVES: [&str; 5] = [
+    "obstacle",
+    "sample",
+    "topology",
+    "diagonal-turns",
+    "collisions",
+];
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RoverStatus {
+    Finished,       // Ran its whole program
+    Active,         // Part way through its program when the snapshot was taken
+    Pending,        // Not deployed yet
+    Failed(String), // The name of the error the next command stops the rover with
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoverSnapshot {
+    pub position: PositionAndHeading,
+    pub program: String, // The commands still to run
+    pub status: RoverStatus,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    pub ur_plateau: (i32, i32),
+    // The mission's options as they stand at the snapshot: sample sites already
+    // collected are gone, and each rover's battery and payload are what it has left.
+    // Custom commands can't be saved, so there are none
+    pub options: MissionOptions,
+    pub rovers: Vec<RoverSnapshot>, // In the order rovers are deployed
+}
+
+fn run_commands(
+    ur_plateau: (i32, i32),
+    options: &MissionOptions,
+    rovers_to_deploy: Vec<(PositionAndHeading, String)>,
+) -> Result<SimulationResult, RoverError> {
+    simulate_rovers_until_failure(
+        InputCommand {
+            ur_plateau,
+            rovers_to_deploy,
+        },
+        options,
+    )
+}
+
+fn remaining_profile(profile: &RoverProfile, report: &RoverReport) -> RoverProfile {
+    // Batteries and payloads carry on from what the rover used before the snapshot
+    RoverProfile {
+        battery: profile
+            .battery
+            .map(|battery| battery.saturating_sub(report.battery_used)),
+        payload: profile
+            .payload
+            .map(|payload| payload.saturating_sub(report.samples.len())),
+        ..profile.clone()
+    }
+}
+
+fn command_steps(commands: &[String], profile: &RoverProfile) -> Vec<usize> {
+    // How many trajectory steps each command takes: moves take one for every cell
+    // driven, and a program that couldn't be split takes however many it needs
+    commands
+        .iter()
+        .map(|command| match command.as_str() {
+            "M" | "B" => profile.speed,
+            command if command.chars().count() == 1 => 1,
+            _ => usize::MAX,
+        })
+        .collect()
+}
+
+fn report_after(
+    report: &RoverReport,
+    command_steps: &[usize],
+    count: usize,
+    options: &MissionOptions,
+    profile: &RoverProfile,
+) -> RoverReport {
+    // The rover's report as it stood after its first `count` commands
+    let steps = command_steps[..count]
+        .iter()
+        .fold(0, |steps: usize, command| steps.saturating_add(*command))
+        .min(report.commands.len());
+    // Samples are only collected by an 'S' on their site
+    let sampled_cells = (0..steps)
+        .filter(|step| report.commands[*step] == 'S')
+        .map(|step| (report.trajectory[step + 1].x, report.trajectory[step + 1].y))
+        .collect::<Vec<_>>();
+    RoverReport {
+        trajectory: report.trajectory[..=steps].to_vec(),
+        commands: report.commands[..steps].to_vec(),
+        samples: report
+            .samples
+            .iter()
+            .filter(|site| sampled_cells.contains(site))
+            .cloned()
+            .collect(),
+        battery_used: report.commands[..steps]
+            .iter()
+            .filter(|command| uses_battery(**command, options, profile))
+            .count(),
+        blocked: None,
+    }
+}
+
+fn commands_completed(report: &RoverReport, command_steps: &[usize]) -> usize {
+    // How many commands a stopped rover got through before the one stopping it
+    let mut steps: usize = 0;
+    command_steps
+        .iter()
+        .take_while(|command| {
+            steps = steps.saturating_add(**command);
+            steps <= report.commands.len()
+        })
+        .count()
+}
+
+pub fn take_snapshot(
+    input_command: InputCommand,
+    options: &MissionOptions,
+    steps: usize,
+) -> Result<Snapshot, RoverError> {
+    // The snapshot is taken after the given number of commands, counting across
+    // rovers in the order they're deployed. Programs with conditions or loops have
+    // no fixed list of commands, so they count as a single step and are never split.
+    // Rovers are only checked against each other where they finish, so the snapshot
+    // is read off a single run of the whole mission rather than runs cut short
+    let ur_plateau = input_command.ur_plateau;
+    let profile = |rover: usize| {
+        options
+            .rover_profiles
+            .get(rover)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let result = simulate_rovers_until_failure(input_command.clone(), options)?;
+    let mut rovers = Vec::new();
+    let mut rover_profiles = Vec::new();
+    // Sample sites collected before the snapshot are taken off the plateau
+    let mut options = options.clone();
+    let mut steps_left = steps;
+    let mut deploying = input_command.rovers_to_deploy.into_iter().enumerate();
+
+    for (index, (_, program)) in deploying.by_ref() {
+        let commands = match expand_program(&program.to_uppercase()) {
+            Ok(commands) => commands.chars().map(|c| c.to_string()).collect::<Vec<_>>(),
+            Err(_) => vec![program.clone()],
+        };
+        let rover_profile = profile(index);
+        let steps_of_commands = command_steps(&commands, &rover_profile);
+        let (report, completed, error) = match &result.failure {
+            Some(failure) if failure.rover == index => (
+                &failure.report,
+                commands_completed(&failure.report, &steps_of_commands),
+                Some(error_name(&failure.error)),
+            ),
+            _ => (&result.reports[index], commands.len(), None),
+        };
+
+        // A rover that stops is caught just before the command that stops it, unless
+        // the snapshot comes before it gets there
+        let (ran, status) = match error {
+            Some(error) if steps_left > completed || steps_left >= commands.len() => {
+                (completed, RoverStatus::Failed(error))
+            }
+            _ if steps_left >= commands.len() => (commands.len(), RoverStatus::Finished),
+            _ if steps_left == 0 => (0, RoverStatus::Pending),
+            _ => (steps_left, RoverStatus::Active),
+        };
+        let report = report_after(report, &steps_of_commands, ran, &options, &rover_profile);
+
+        rover_profiles.push(remaining_profile(&rover_profile, &report));
+        options
+            .sample_sites
+            .retain(|site| !report.samples.contains(site));
+        rovers.push(RoverSnapshot {
+            position: report.trajectory[report.trajectory.len() - 1].clone(),
+            program: commands[ran..].concat(),
+            status: status.clone(),
+        });
+        if status != RoverStatus::Finished {
+            break;
+        }
+        steps_left -= ran;
+    }
+
+    for (index, (position, program)) in deploying {
+        rover_profiles.push(profile(index));
+        rovers.push(RoverSnapshot {
+            position,
+            program,
+            status: RoverStatus::Pending,
+        });
+    }
+    // Rovers without a profile get the default, so those at the end are left out
+    while rover_profiles.last() == Some(&RoverProfile::default()) {
+        rover_profiles.pop();
+    }
+    Ok(Snapshot {
+        ur_plateau,
+        options: MissionOptions {
+            rover_profiles,
+            commands: CommandRegistry::new(),
+            ..options
+        },
+        rovers,
+    })
+}
+
+pub fn resume_snapshot(
+    snapshot: &Snapshot,
+    commands: &CommandRegistry,
+) -> Result<SimulationResult, RoverError> {
+    // Finished rovers are parked where they stopped and every other rover runs the
+    // rest of its program from its snapshot position, with the mission's options as
+    // they stood. Custom commands aren't saved, so they're given again
+    let options = MissionOptions {
+        commands: commands.clone(),
+        ..snapshot.options.clone()
+    };
+    let rovers_to_deploy = snapshot
+        .rovers
+        .iter()
+        .map(|rover| {
+            let program = match rover.status {
+                RoverStatus::Finished => String::new(),
+                _ => rover.program.clone(),
+            };
+            (rover.position.clone(), program)
+        })
+        .collect();
+    run_commands(snapshot.ur_plateau, &options, rovers_to_deploy)
+}
+
+fn status_name(status: &RoverStatus) -> &str {
+    match status {
+        RoverStatus::Finished => "finished",
+        RoverStatus::Active => "active",
+        RoverStatus::Pending => "pending",
+        RoverStatus::Failed(_) => "failed",
+    }
+}
+
+fn parse_status(name: &str, error: Option<String>) -> Option<RoverStatus> {
+    match (name, error) {
+        ("finished", _) => Some(RoverStatus::Finished),
+        ("active", _) => Some(RoverStatus::Active),
+        ("pending", _) => Some(RoverStatus::Pending),
+        ("failed", Some(error)) => Some(RoverStatus::Failed(error)),
+        _ => None,
+    }
+}
+
+pub fn format_snapshot(snapshot: &Snapshot) -> String {
+    // One line per item, with each rover's program at the end of its line as it can hold spaces
+    // Options that differ from the default are written as mission file directives,
+    // and a rover's type follows its line when it has one
+    let options = &snapshot.options;
+    let mut formatted = format!(
+        "plateau {} {}\n",
+        snapshot.ur_plateau.0, snapshot.ur_plateau.1
+    );
+    if options.topology == Topology::Hex {
+        formatted.push_str("topology hex\n");
+    }
+    if options.diagonal_turns != DEFAULT_DIAGONAL_TURNS {
+        formatted.push_str(&format!(
+            "diagonal-turns {} {}\n",
+            options.diagonal_turns.0, options.diagonal_turns.1
+        ));
+    }
+    if options.path_collisions {
+        formatted.push_str("collisions path\n");
+    }
+    for (x, y) in &options.obstacles {
+        formatted.push_str(&format!("obstacle {} {}\n", x, y));
+    }
+    for (x, y) in &options.sample_sites {
+        formatted.push_str(&format!("sample {} {}\n", x, y));
+    }
+    for (index, rover) in snapshot.rovers.iter().enumerate() {
+        // Failed rovers have the error they stopped with after the status
+        let status = match &rover.status {
+            RoverStatus::Failed(error) => format!("failed {}", error),
+            status => status_name(status).to_string(),
+        };
+        let line = format!(
+            "rover {} {} {} {} {}",
+            rover.position.x,
+            rover.position.y,
+            heading_name(rover.position.heading),
+            status,
+            rover.program
+        );
+        formatted.push_str(line.trim_end());
+        formatted.push('\n');
+        let capabilities = options
+            .rover_profiles
+            .get(index)
+            .map(format_capabilities)
+            .unwrap_or_default();
+        if !capabilities.is_empty() {
+            formatted.push_str(&format!("type {}\n", capabilities.join(" ")));
+        }
+    }
+    formatted
+}
+
+fn parse_snapshot_line(line: &str, snapshot: &mut Snapshot) -> Option<()> {
+    // Mission directives are read once the plateau is known
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    match words.as_slice() {
+        [] => {}
+        ["plateau", x, y] => snapshot.ur_plateau = (x.parse().ok()?, y.parse().ok()?),
+        [keyword, ..] if SNAPSHOT_DIRECTIVES.contains(keyword) => {}
+        ["type", capabilities @ ..] if !capabilities.is_empty() => {
+            // A type is for the rover on the line before
+            let rover = snapshot.rovers.len().checked_sub(1)?;
+            let profiles = &mut snapshot.options.rover_profiles;
+            if profiles.len() > rover {
+                return None;
+            }
+            profiles.resize(rover + 1, RoverProfile::default());
+            for capability in capabilities {
+                parse_capability(&mut profiles[rover], capability)?;
+            }
+        }
+        ["rover", x, y, heading, status, rest @ ..] => {
+            let (status, program) = match (*status, rest) {
+                ("failed", [error, program @ ..]) => {
+                    (parse_status("failed", Some(error.to_string()))?, program)
+                }
+                (status, program) => (parse_status(status, None)?, program),
+            };
+            snapshot.rovers.push(RoverSnapshot {
+                position: PositionAndHeading {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                    heading: parse_heading(heading)?,
+                },
+                program: program.join(" "),
+                status,
+            });
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+pub fn parse_snapshot(text: &str) -> Result<Snapshot, RoverError> {
+    let mut snapshot = Snapshot {
+        ur_plateau: (0, 0),
+        options: MissionOptions::default(),
+        rovers: Vec::new(),
+    };
+    for (index, line) in text.lines().enumerate() {
+        parse_snapshot_line(line, &mut snapshot)
+            .ok_or(RoverError::InvalidOption { line: index + 1 })?;
+    }
+    if snapshot.rovers.is_empty() {
+        return Err(RoverError::InvalidNumberOfCommandsForRover);
+    }
+
+    // Every other line is left blank, so errors give the line in the snapshot
+    let directives = text
+        .lines()
+        .map(|line| match line.split_whitespace().next() {
+            Some(keyword) if SNAPSHOT_DIRECTIVES.contains(&keyword) => line.to_string(),
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>();
+    snapshot.options = MissionOptions {
+        rover_profiles: snapshot.options.rover_profiles,
+        ..parse_options(&directives, snapshot.ur_plateau)?
+    };
+    Ok(snapshot)
+}
+
+pub fn format_snapshot_json(snapshot: &Snapshot) -> String {
+    let cell = |(x, y): (i32, i32)| {
+        JsonValue::Array(vec![
+            JsonValue::Number(x as f64),
+            JsonValue::Number(y as f64),
+        ])
+    };
+    let options = &snapshot.options;
+    let rovers = snapshot
+        .rovers
+        .iter()
+        .enumerate()
+        .map(|(index, rover)| {
+            let mut members = vec![
+                ("x".to_string(), JsonValue::Number(rover.position.x as f64)),
+                ("y".to_string(), JsonValue::Number(rover.position.y as f64)),
+                (
+                    "heading".to_string(),
+                    JsonValue::String(heading_name(rover.position.heading)),
+                ),
+                (
+                    "status".to_string(),
+                    JsonValue::String(status_name(&rover.status).to_string()),
+                ),
+                (
+                    "program".to_string(),
+                    JsonValue::String(rover.program.clone()),
+                ),
+            ];
+            if let RoverStatus::Failed(error) = &rover.status {
+                members.push(("error".to_string(), JsonValue::String(error.clone())));
+            }
+            if let Some(profile) = options.rover_profiles.get(index) {
+                if *profile != RoverProfile::default() {
+                    members.push(("type".to_string(), rover_type_json(profile)));
+                }
+            }
+            JsonValue::Object(members)
+        })
+        .collect();
+
+    // Options are written the same as in JSON missions, leaving out the defaults
+    let cells = |cells: &[(i32, i32)]| JsonValue::Array(cells.iter().map(|c| cell(*c)).collect());
+    let mut option_members = vec![("obstacles".to_string(), cells(&options.obstacles))];
+    if !options.sample_sites.is_empty() {
+        option_members.push(("samples".to_string(), cells(&options.sample_sites)));
+    }
+    if options.topology == Topology::Hex {
+        option_members.push(("topology".to_string(), JsonValue::String("hex".to_string())));
+    }
+    if options.path_collisions {
+        option_members.push((
+            "collisions".to_string(),
+            JsonValue::String("path".to_string()),
+        ));
+    }
+    if options.diagonal_turns != DEFAULT_DIAGONAL_TURNS {
+        let (left, right) = options.diagonal_turns;
+        option_members.push((
+            "diagonal_turns".to_string(),
+            JsonValue::Array(vec![
+                JsonValue::String(left.to_string()),
+                JsonValue::String(right.to_string()),
+            ]),
+        ));
+    }
+    let snapshot = JsonValue::Object(vec![
+        ("plateau".to_string(), cell(snapshot.ur_plateau)),
+        ("options".to_string(), JsonValue::Object(option_members)),
+        ("rovers".to_string(), JsonValue::Array(rovers)),
+    ]);
+    format!("{}\n", format_json(&snapshot))
+}
+
+pub fn parse_snapshot_json(text: &str) -> Result<Snapshot, RoverError> {
+    let json = parse_json(text)?;
+    let ur_plateau = match json.get("plateau").and_then(|plateau| plateau.as_array()) {
+        Some([x, y]) => x
+            .as_i32()
+            .zip(y.as_i32())
+            .ok_or(RoverError::InvalidPlateau)?,
+        _ => return Err(RoverError::InvalidPlateau),
+    };
+    let (mut options, _) = json_options(json.get("options"), ur_plateau)?;
+
+    let rovers = match json.get("rovers").and_then(|rovers| rovers.as_array()) {
+        Some(rovers) if !rovers.is_empty() => rovers,
+        _ => return Err(RoverError::InvalidNumberOfCommandsForRover),
+    };
+    let mut snapshot_rovers = Vec::new();
+    for (index, rover) in rovers.iter().enumerate() {
+        let path = |key: &str| format!("rovers[{}].{}", index, key);
+        let field = |key: &str| {
+            rover
+                .get(key)
+                .ok_or_else(|| invalid_json(&path(key), "is missing"))
+        };
+        let text_field = |key: &str| {
+            field(key)?
+                .as_str()
+                .map(|text| text.to_string())
+                .ok_or_else(|| invalid_json(&path(key), "must be text"))
+        };
+        let number_field = |key: &str| {
+            field(key)?
+                .as_i32()
+                .ok_or_else(|| invalid_json(&path(key), "must be a whole number"))
+        };
+
+        let status = parse_status(&text_field("status")?, text_field("error").ok())
+            .ok_or_else(|| invalid_json(&path("status"), "is invalid"))?;
+        if let Some(rover_type) = rover.get("type") {
+            options
+                .rover_profiles
+                .resize(index + 1, RoverProfile::default());
+            options.rover_profiles[index] = json_rover_type(rover_type, &path("type"))?;
+        }
+        snapshot_rovers.push(RoverSnapshot {
+            position: PositionAndHeading {
+                x: number_field("x")?,
+                y: number_field("y")?,
+                heading: parse_heading(&text_field("heading")?)
+                    .ok_or_else(|| invalid_json(&path("heading"), "is invalid"))?,
+            },
+            program: text_field("program")?,
+            status,
+        });
+    }
+
+    Ok(Snapshot {
+        ur_plateau,
+        options,
+        rovers: snapshot_rovers,
+    })
+}
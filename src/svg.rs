@@ -0,0 +1,163 @@
+use crate::check::error_name;
+use crate::rover::{
+    PositionAndHeading, SimulationResult, NORTH_EAST, NORTH_WEST, SOUTH_EAST, SOUTH_WEST,
+};
+
+// Pixels per plateau cell, and around the edge of the drawing, which leaves room
+// to mark a cell just off the plateau
+const CELL_SIZE: i32 = 40;
+const MARGIN: i32 = 40;
+
+// Each rover's color, reused from the start once every color is taken
+const ROVER_COLORS: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2", "#17becf", "#bcbd22",
+];
+const FAILURE_COLOR: &str = "#d62728";
+
+fn heading_angle(heading: char) -> i32 {
+    // Degrees clockwise from North, matching SVG's clockwise rotation
+    match heading {
+        NORTH_EAST => 45,
+        'E' => 90,
+        SOUTH_EAST => 135,
+        'S' => 180,
+        SOUTH_WEST => 225,
+        'W' => 270,
+        NORTH_WEST => 315,
+        _ => 0,
+    }
+}
+
+fn cell_center(ur_plateau: (i32, i32), (x, y): (i32, i32)) -> (i32, i32) {
+    // SVG's y axis grows downwards, so north is flipped to the top
+    (
+        MARGIN + x * CELL_SIZE + CELL_SIZE / 2,
+        MARGIN + (ur_plateau.1 - y) * CELL_SIZE + CELL_SIZE / 2,
+    )
+}
+
+fn draw_grid(ur_plateau: (i32, i32), obstacles: &[(i32, i32)]) -> String {
+    let (width, height) = (
+        (ur_plateau.0 + 1) * CELL_SIZE,
+        (ur_plateau.1 + 1) * CELL_SIZE,
+    );
+    let mut svg = format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#fdf6ec\" stroke=\"#999\"/>\n",
+        MARGIN, MARGIN, width, height
+    );
+    for x in 1..=ur_plateau.0 {
+        let line_x = MARGIN + x * CELL_SIZE;
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#ddd\"/>\n",
+            line_x,
+            MARGIN,
+            line_x,
+            MARGIN + height
+        ));
+    }
+    for y in 1..=ur_plateau.1 {
+        let line_y = MARGIN + y * CELL_SIZE;
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#ddd\"/>\n",
+            MARGIN,
+            line_y,
+            MARGIN + width,
+            line_y
+        ));
+    }
+    for obstacle in obstacles {
+        let (center_x, center_y) = cell_center(ur_plateau, *obstacle);
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#666\"/>\n",
+            center_x - CELL_SIZE / 2,
+            center_y - CELL_SIZE / 2,
+            CELL_SIZE,
+            CELL_SIZE
+        ));
+    }
+    svg
+}
+
+fn draw_trajectory(
+    ur_plateau: (i32, i32),
+    rover: usize,
+    trajectory: &[PositionAndHeading],
+) -> String {
+    let color = ROVER_COLORS[rover % ROVER_COLORS.len()];
+    let points = trajectory
+        .iter()
+        .map(|position| {
+            let (x, y) = cell_center(ur_plateau, (position.x, position.y));
+            format!("{},{}", x, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut svg = format!(
+        "<g id=\"rover-{}\">\n<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"3\"/>\n",
+        rover + 1,
+        points,
+        color
+    );
+
+    // The start is a hollow circle and the end an arrow pointing the final heading
+    let start = &trajectory[0];
+    let (start_x, start_y) = cell_center(ur_plateau, (start.x, start.y));
+    svg.push_str(&format!(
+        "<circle cx=\"{}\" cy=\"{}\" r=\"8\" fill=\"white\" stroke=\"{}\" stroke-width=\"3\"/>\n",
+        start_x, start_y, color
+    ));
+    let end = &trajectory[trajectory.len() - 1];
+    let (end_x, end_y) = cell_center(ur_plateau, (end.x, end.y));
+    svg.push_str(&format!(
+        "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\" transform=\"rotate({} {} {})\"/>\n",
+        end_x,
+        end_y - 12,
+        end_x - 9,
+        end_y + 9,
+        end_x + 9,
+        end_y + 9,
+        color,
+        heading_angle(end.heading),
+        end_x,
+        end_y
+    ));
+    svg.push_str("</g>\n");
+    svg
+}
+
+pub fn render_svg(
+    ur_plateau: (i32, i32),
+    obstacles: &[(i32, i32)],
+    result: &SimulationResult,
+) -> String {
+    let width = (ur_plateau.0 + 1).max(0) * CELL_SIZE + 2 * MARGIN;
+    let height = (ur_plateau.1 + 1).max(0) * CELL_SIZE + 2 * MARGIN;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    svg.push_str(&draw_grid(ur_plateau, obstacles));
+
+    for (rover, report) in result.reports.iter().enumerate() {
+        svg.push_str(&draw_trajectory(ur_plateau, rover, &report.trajectory));
+    }
+    // The rover that failed is drawn up to where it was stopped, and the cell it
+    // was stopped from driving into is ringed, or where it stopped if there's none
+    if let Some(failure) = &result.failure {
+        let trajectory = &failure.report.trajectory;
+        svg.push_str(&draw_trajectory(ur_plateau, failure.rover, trajectory));
+        let stopped = &trajectory[trajectory.len() - 1];
+        let blocked = failure.report.blocked.unwrap_or((stopped.x, stopped.y));
+        let (x, y) = cell_center(ur_plateau, blocked);
+        svg.push_str(&format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"16\" fill=\"none\" stroke=\"{}\" stroke-width=\"4\"><title>{}</title></circle>\n",
+            x,
+            y,
+            FAILURE_COLOR,
+            error_name(&failure.error)
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
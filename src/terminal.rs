@@ -0,0 +1,71 @@
+use std::io::{self, IsTerminal, Read};
+use std::process::{Command, Stdio};
+
+// Ctrl+C, read as a key rather than stopping the program while keys are read as
+// they're pressed, so the terminal's settings are always restored
+pub const INTERRUPT: char = '\u{3}';
+
+// Puts the terminal into a mode where keys are read as they're pressed, without
+// waiting for enter or echoing them, until dropped. Uses 'stty', so there's no
+// key handling where it isn't available or stdin isn't a terminal
+pub struct RawMode {
+    // The terminal's settings before it was put in raw mode, in the form printed
+    // by 'stty -g', or None if it never was
+    saved_settings: Option<String>,
+}
+
+fn stty(args: &[&str]) -> bool {
+    Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn stty_settings() -> Option<String> {
+    let output = Command::new("stty")
+        .arg("-g")
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+impl RawMode {
+    // With 'blocking' unset, reading a key returns straight away when none was pressed
+    pub fn enable(blocking: bool) -> RawMode {
+        let min = if blocking { "1" } else { "0" };
+        let saved_settings = if io::stdin().is_terminal() {
+            stty_settings()
+        } else {
+            None
+        };
+        let saved_settings = saved_settings
+            .filter(|_| stty(&["-icanon", "-echo", "-isig", "min", min, "time", "0"]));
+        RawMode { saved_settings }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.saved_settings.is_some()
+    }
+
+    pub fn read_key(&self) -> Option<char> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut key = [0; 1];
+        match io::stdin().read(&mut key) {
+            Ok(1) => Some(key[0] as char),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved_settings) = &self.saved_settings {
+            stty(&[saved_settings]);
+        }
+    }
+}
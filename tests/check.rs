@@ -0,0 +1,105 @@
+use rusty_rover::check::{check_mission, format_check_report, rover_outcomes, Outcome};
+use rusty_rover::mission::parse_mission;
+use rusty_rover::rover::{PositionAndHeading, RoverError};
+
+fn mission_lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+fn finished(x: i32, y: i32, heading: char) -> Outcome {
+    Outcome::Finished(PositionAndHeading { x, y, heading })
+}
+
+#[test]
+fn test_parse_mission_expectations() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "1 2 N",
+        "LMLMLMLMM",
+        "3 3 E",
+        "MMRMMRMRRM",
+        "expect:",
+        "1 3 n",
+        "",
+        "error Collision",
+    ]);
+    let invalid_expectation = mission_lines(&["5 5", "1 2 N", "M", "expect:", "1 3"]);
+    let extra_expectation = mission_lines(&["5 5", "1 2 N", "M", "expect:", "1 3 N", "1 4 N"]);
+
+    assert_eq!(
+        parse_mission(test_input).unwrap().expectations,
+        vec![
+            finished(1, 3, 'N'),
+            Outcome::Failed("Collision".to_string())
+        ]
+    );
+    assert_eq!(
+        parse_mission(invalid_expectation).err(),
+        Some(RoverError::InvalidOption { line: 5 })
+    );
+    assert_eq!(
+        parse_mission(extra_expectation).err(),
+        Some(RoverError::InvalidOption { line: 4 })
+    );
+}
+
+#[test]
+fn test_rover_outcomes_stop_at_first_failure() {
+    let test_input = mission_lines(&["5 5", "1 2 N", "M", "3 3 E", "MMMM", "0 0 N", "M"]);
+
+    let mission = parse_mission(test_input).unwrap();
+    assert_eq!(
+        rover_outcomes(&mission),
+        Ok(vec![
+            finished(1, 3, 'N'),
+            Outcome::Failed("OutOfBounds".to_string()),
+            Outcome::NotDeployed,
+        ])
+    );
+}
+
+#[test]
+fn test_rover_outcomes_of_invalid_program() {
+    // The second rover's program can't be read, so no rover is reported as finished
+    let test_input = mission_lines(&["5 5", "1 2 N", "M", "3 3 E", "MQ", "0 0 N", "M"]);
+
+    let mission = parse_mission(test_input).unwrap();
+    assert_eq!(rover_outcomes(&mission), Err(RoverError::InvalidMove));
+
+    // Checking reports it against the rover, so the error can be expected
+    let actual = check_mission(&mission)
+        .unwrap()
+        .into_iter()
+        .map(|check| check.actual)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        vec![
+            finished(1, 3, 'N'),
+            Outcome::Failed("InvalidMove".to_string()),
+            Outcome::NotDeployed,
+        ]
+    );
+}
+
+#[test]
+fn test_check_mission() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "1 2 N",
+        "LMLMLMLMM",
+        "3 3 E",
+        "MMRMMRMRRM",
+        "expect:",
+        "1 3 N",
+        "5 1 N",
+    ]);
+
+    let checks = check_mission(&parse_mission(test_input).unwrap()).unwrap();
+    assert_eq!(checks[1].expected, finished(5, 1, 'N'));
+    assert_eq!(checks[1].actual, finished(5, 1, 'E'));
+    assert_eq!(
+        format_check_report(&checks),
+        "Rover 1: ok (1 3 N)\nRover 2: expected 5 1 N, got 5 1 E\n\n1/2 rover(s) as expected\n"
+    );
+}
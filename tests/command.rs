@@ -0,0 +1,280 @@
+use std::sync::{Arc, Mutex};
+
+use rusty_rover::check::Outcome;
+use rusty_rover::command::{Command, CommandRegistry};
+use rusty_rover::mission_log::{format_events, mission_events, replay};
+use rusty_rover::rover::{
+    simulate_rover_move_with_options, simulate_rovers_until_failure, InputCommand, MissionOptions,
+    PositionAndHeading, RoverError, RoverProfile, NORTH_WEST,
+};
+
+// Records where each rover drilled, leaving it where it is
+struct Drill {
+    holes: Arc<Mutex<Vec<(usize, i32, i32)>>>,
+}
+
+impl Command for Drill {
+    fn letter(&self) -> char {
+        'D'
+    }
+
+    fn apply(
+        &self,
+        rover: usize,
+        position_and_heading: &PositionAndHeading,
+    ) -> Result<PositionAndHeading, RoverError> {
+        let (x, y) = (position_and_heading.x, position_and_heading.y);
+        self.holes.lock().unwrap().push((rover, x, y));
+        Ok(position_and_heading.clone())
+    }
+}
+
+// Jumps two cells East whatever the rover's heading
+struct Hop;
+
+impl Command for Hop {
+    fn letter(&self) -> char {
+        'H'
+    }
+
+    fn apply(
+        &self,
+        _rover: usize,
+        position_and_heading: &PositionAndHeading,
+    ) -> Result<PositionAndHeading, RoverError> {
+        Ok(PositionAndHeading {
+            x: position_and_heading.x + 2,
+            ..position_and_heading.clone()
+        })
+    }
+}
+
+fn rover_with_program(x: i32, y: i32, program: &str) -> (PositionAndHeading, String) {
+    (
+        PositionAndHeading { x, y, heading: 'N' },
+        program.to_string(),
+    )
+}
+
+#[test]
+fn test_register_rejects_built_in_and_duplicate_letters() {
+    struct Mover;
+    impl Command for Mover {
+        fn letter(&self) -> char {
+            'M'
+        }
+        fn apply(
+            &self,
+            _rover: usize,
+            position_and_heading: &PositionAndHeading,
+        ) -> Result<PositionAndHeading, RoverError> {
+            Ok(position_and_heading.clone())
+        }
+    }
+
+    let mut registry = CommandRegistry::new();
+    assert_eq!(registry.register(Hop), Ok(()));
+    assert_eq!(registry.register(Hop), Err(RoverError::InvalidCommand('H')));
+    assert_eq!(
+        registry.register(Mover),
+        Err(RoverError::InvalidCommand('M'))
+    );
+    assert_eq!(registry.letters(), vec!['H']);
+}
+
+#[test]
+fn test_custom_command_runs_in_program() {
+    let holes = Arc::new(Mutex::new(Vec::new()));
+    let mut options = MissionOptions::default();
+    options
+        .commands
+        .register(Drill {
+            holes: holes.clone(),
+        })
+        .unwrap();
+
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            rover_with_program(1, 1, "dM2(DM)"),
+            rover_with_program(4, 4, "if !edge { D }"),
+        ],
+    };
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 4,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 4,
+            y: 4,
+            heading: 'N',
+        },
+    ];
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &options),
+        Ok(expected_output)
+    );
+    assert_eq!(
+        *holes.lock().unwrap(),
+        vec![(0, 1, 1), (0, 1, 2), (0, 1, 3), (1, 4, 4)]
+    );
+}
+
+#[test]
+fn test_custom_command_checked_like_a_move() {
+    let mut options = MissionOptions::default();
+    options.commands.register(Hop).unwrap();
+    options.obstacles.push((3, 0));
+
+    let unregistered = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_with_program(0, 0, "MH")],
+    };
+    let over_obstacle = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_with_program(2, 0, "H")],
+    };
+    let into_obstacle = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_with_program(1, 0, "H")],
+    };
+    let off_plateau = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_with_program(2, 1, "HH")],
+    };
+
+    assert_eq!(
+        simulate_rover_move_with_options(unregistered, &MissionOptions::default()),
+        Err(RoverError::InvalidMove)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(into_obstacle, &options),
+        Err(RoverError::Collision)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(off_plateau, &options),
+        Err(RoverError::OutOfBounds)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(over_obstacle, &options),
+        Ok(vec![PositionAndHeading {
+            x: 4,
+            y: 0,
+            heading: 'N',
+        }])
+    );
+}
+
+// Marks the rover's cell, leaving it where it is
+struct Flag;
+
+impl Command for Flag {
+    fn letter(&self) -> char {
+        'A'
+    }
+
+    fn apply(
+        &self,
+        _rover: usize,
+        position_and_heading: &PositionAndHeading,
+    ) -> Result<PositionAndHeading, RoverError> {
+        Ok(position_and_heading.clone())
+    }
+}
+
+#[test]
+fn test_custom_command_on_diagonal_turn_letter() {
+    let mut options = MissionOptions {
+        rover_profiles: vec![
+            RoverProfile::default(),
+            RoverProfile {
+                diagonal: true,
+                ..RoverProfile::default()
+            },
+        ],
+        ..MissionOptions::default()
+    };
+    options.commands.register(Flag).unwrap();
+
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            rover_with_program(1, 1, "AM"),
+            rover_with_program(3, 3, "AM"),
+        ],
+    };
+
+    // Only the diagonal rover turns, the other one runs the custom command
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 2,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 2,
+            y: 4,
+            heading: NORTH_WEST,
+        },
+    ];
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &options),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_mission_options_can_be_shared_between_threads() {
+    fn assert_send_and_sync<T: Send + Sync>(_: &T) {}
+
+    let mut options = MissionOptions::default();
+    options.commands.register(Hop).unwrap();
+    assert_send_and_sync(&options);
+}
+
+#[test]
+fn test_custom_commands_are_logged_and_replayed() {
+    let mut commands = CommandRegistry::new();
+    commands.register(Hop).unwrap();
+    let options = MissionOptions {
+        commands,
+        ..MissionOptions::default()
+    };
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: 'N',
+            },
+            "HM".to_string(),
+        )],
+    };
+
+    let result = simulate_rovers_until_failure(input_command, &options).unwrap();
+    let log = format_events(&mission_events(&result));
+    assert_eq!(
+        log.lines().nth(1),
+        Some(r#"{"event":"moved","rover":1,"command":"H","x":2,"y":0,"heading":"N"}"#)
+    );
+    assert_eq!(
+        replay(&log, &options),
+        Ok(vec![Outcome::Finished(PositionAndHeading {
+            x: 2,
+            y: 1,
+            heading: 'N',
+        })])
+    );
+
+    // Without the command the hop can't be replayed
+    assert_eq!(
+        replay(&log, &MissionOptions::default()),
+        Err(RoverError::InvalidJson(
+            "line 2: rover 1 can't make that step with 'H'".to_string()
+        ))
+    );
+}
@@ -0,0 +1,165 @@
+use rusty_rover::coverage::{
+    coverage_percentage, format_coverage_map, is_cell_in_view, map_rover_coverage, Camera,
+};
+use rusty_rover::rover::{
+    InputCommand, MissionOptions, PositionAndHeading, RoverError, RoverProfile, Topology,
+    NORTH_EAST,
+};
+
+#[test]
+fn test_cell_in_view_ahead_of_rover() {
+    let position = PositionAndHeading {
+        x: 2,
+        y: 2,
+        heading: 'N',
+    };
+    let camera = Camera {
+        range: 2,
+        field_of_view: 90,
+    };
+
+    assert!(is_cell_in_view(&position, &camera, (2, 2)));
+    assert!(is_cell_in_view(&position, &camera, (2, 4)));
+    assert!(is_cell_in_view(&position, &camera, (3, 3)));
+    assert!(!is_cell_in_view(&position, &camera, (2, 5)));
+    assert!(!is_cell_in_view(&position, &camera, (3, 2)));
+    assert!(!is_cell_in_view(&position, &camera, (2, 1)));
+}
+
+#[test]
+fn test_cell_in_view_wide_camera_sees_behind() {
+    let position = PositionAndHeading {
+        x: 2,
+        y: 2,
+        heading: 'E',
+    };
+    let camera = Camera {
+        range: 1,
+        field_of_view: 360,
+    };
+
+    assert!(is_cell_in_view(&position, &camera, (1, 2)));
+    assert!(is_cell_in_view(&position, &camera, (2, 1)));
+    assert!(!is_cell_in_view(&position, &camera, (0, 2)));
+}
+
+#[test]
+fn test_coverage_map_single_rover() {
+    let test_input = InputCommand {
+        ur_plateau: (2, 2),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: 'N',
+            },
+            "M".to_string(),
+        )],
+    };
+    let cameras = vec![Camera {
+        range: 1,
+        field_of_view: 0,
+    }];
+
+    let coverage = map_rover_coverage(test_input, &MissionOptions::default(), &cameras).unwrap();
+    assert_eq!(format_coverage_map(&coverage), "#..\n#..\n#..\n");
+    assert!((coverage_percentage(&coverage) - 100.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_coverage_map_uses_camera_per_rover() {
+    let test_input = InputCommand {
+        ur_plateau: (2, 0),
+        rovers_to_deploy: vec![
+            (
+                PositionAndHeading {
+                    x: 0,
+                    y: 0,
+                    heading: 'E',
+                },
+                "".to_string(),
+            ),
+            (
+                PositionAndHeading {
+                    x: 2,
+                    y: 0,
+                    heading: 'W',
+                },
+                "".to_string(),
+            ),
+        ],
+    };
+    let cameras = vec![
+        Camera {
+            range: 0,
+            field_of_view: 90,
+        },
+        Camera {
+            range: 1,
+            field_of_view: 90,
+        },
+    ];
+
+    let coverage = map_rover_coverage(test_input, &MissionOptions::default(), &cameras).unwrap();
+    assert_eq!(coverage.observed, vec![vec![true, true, true]]);
+    assert!((coverage_percentage(&coverage) - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_coverage_map_reports_simulation_errors() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: 'S',
+            },
+            "MMMMMMM".to_string(),
+        )],
+    };
+
+    assert_eq!(
+        map_rover_coverage(test_input, &MissionOptions::default(), &[]),
+        Err(RoverError::OutOfBounds)
+    );
+}
+
+#[test]
+fn test_coverage_map_uses_mission_options() {
+    let test_input = InputCommand {
+        ur_plateau: (2, 2),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: NORTH_EAST,
+            },
+            "M".to_string(),
+        )],
+    };
+    let cameras = vec![Camera {
+        range: 0,
+        field_of_view: 90,
+    }];
+
+    // Only a diagonal rover can head North East
+    let options = MissionOptions {
+        rover_profiles: vec![RoverProfile {
+            diagonal: true,
+            ..RoverProfile::default()
+        }],
+        ..MissionOptions::default()
+    };
+    let coverage = map_rover_coverage(test_input.clone(), &options, &cameras).unwrap();
+    assert_eq!(format_coverage_map(&coverage), "...\n.#.\n#..\n");
+
+    let options = MissionOptions {
+        topology: Topology::Hex,
+        ..options
+    };
+    assert_eq!(
+        map_rover_coverage(test_input, &options, &cameras),
+        Err(RoverError::InvalidTopology)
+    );
+}
@@ -0,0 +1,27 @@
+use rusty_rover::history::History;
+
+#[test]
+fn test_history_undo_redo() {
+    let mut history = History::new(0);
+    history.record(1);
+    history.record(2);
+
+    assert_eq!(history.undo(), Some(&1));
+    assert_eq!(history.undo(), Some(&0));
+    assert_eq!(history.undo(), None);
+    assert_eq!(history.current(), &0);
+    assert_eq!(history.redo(), Some(&1));
+    assert_eq!(history.current(), &1);
+}
+
+#[test]
+fn test_history_record_drops_redo() {
+    let mut history = History::new("a");
+    history.record("b");
+    history.undo();
+    history.record("c");
+
+    assert_eq!(history.redo(), None);
+    assert_eq!(history.undo(), Some(&"a"));
+    assert_eq!(history.redo(), Some(&"c"));
+}
@@ -0,0 +1,155 @@
+use rusty_rover::json::{format_json, parse_json, JsonValue};
+use rusty_rover::mission::{parse_json_mission, parse_mission, simulate_mission};
+use rusty_rover::rover::{PositionAndHeading, RoverError, RoverProfile, Topology};
+
+#[test]
+fn test_parse_json_values() {
+    let test_input = r#" {"a": [1, -2.5e1, true, null], "b": "N\"Eé", "c": {}} "#;
+
+    let expected_output = JsonValue::Object(vec![
+        (
+            "a".to_string(),
+            JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Number(-25.0),
+                JsonValue::Bool(true),
+                JsonValue::Null,
+            ]),
+        ),
+        ("b".to_string(), JsonValue::String("N\"E\u{e9}".to_string())),
+        ("c".to_string(), JsonValue::Object(Vec::new())),
+    ]);
+    assert_eq!(parse_json(test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_parse_json_rejects_malformed_input() {
+    for test_input in &["", "[1, 2", "{\"a\" 1}", "[1,]", "\"abc", "tru", "[1] 2"] {
+        assert!(matches!(
+            parse_json(test_input),
+            Err(RoverError::InvalidJson(_))
+        ));
+    }
+    assert_eq!(
+        parse_json("[1, 2"),
+        Err(RoverError::InvalidJson(
+            "expected ',' or ']' at character 5".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_format_json_round_trips() {
+    let test_input = r#"{"a":[1,-2.5,true,null],"b":"N\"E\n\u0001","c":{}}"#;
+
+    let value = parse_json(test_input).unwrap();
+    assert_eq!(format_json(&value), test_input);
+    assert_eq!(parse_json(&format_json(&value)), Ok(value));
+}
+
+#[test]
+fn test_json_mission_matches_text_mission() {
+    let json_input = r#"{
+        "plateau": [5, 5],
+        "rovers": [
+            {"x": 1, "y": 2, "heading": "N", "program": "LMLMLMLMM"},
+            {"x": 3, "y": 3, "heading": "E", "program": "MMRMMRMRRM"}
+        ]
+    }"#;
+    let text_input = ["5 5", "1 2 N", "LMLMLMLMM", "3 3 E", "MMRMMRMRRM"]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+    let json_mission = parse_json_mission(json_input).unwrap();
+    assert_eq!(json_mission, parse_mission(text_input).unwrap());
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 3,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 5,
+            y: 1,
+            heading: 'E',
+        },
+    ];
+    assert_eq!(simulate_mission(json_mission), Ok(expected_output));
+}
+
+#[test]
+fn test_json_mission_options() {
+    let test_input = r#"{
+        "plateau": [5, 5],
+        "options": {
+            "obstacles": [[2, 2]],
+            "samples": [[1, 3]],
+            "topology": "hex",
+            "types": {"scout": {"speed": 2, "commands": "lrm", "payload": 1}}
+        },
+        "rovers": [
+            {"x": 1, "y": 2, "heading": "NE", "program": "M"},
+            {"x": 3, "y": 3, "heading": "E", "type": "SCOUT"}
+        ],
+        "expect": ["1 3 NE"]
+    }"#;
+
+    let mission = parse_json_mission(test_input).unwrap();
+    assert_eq!(mission.options.obstacles, vec![(2, 2)]);
+    assert_eq!(mission.options.sample_sites, vec![(1, 3)]);
+    assert_eq!(mission.options.topology, Topology::Hex);
+    assert_eq!(
+        mission.options.rover_profiles,
+        vec![
+            RoverProfile::default(),
+            RoverProfile {
+                speed: 2,
+                allowed_commands: Some(vec!['L', 'R', 'M']),
+                payload: Some(1),
+                ..RoverProfile::default()
+            },
+        ]
+    );
+    assert_eq!(mission.input_command.rovers_to_deploy[1].1, "");
+    assert_eq!(mission.expectations.len(), 1);
+}
+
+#[test]
+fn test_json_mission_errors() {
+    let no_plateau = r#"{"rovers": [{"x": 1, "y": 2, "heading": "N"}]}"#;
+    let no_rovers = r#"{"plateau": [5, 5], "rovers": []}"#;
+    let missing_heading = r#"{"plateau": [5, 5], "rovers": [{"x": 1, "y": 2}]}"#;
+    let off_plateau = r#"{"plateau": [5, 5], "options": {"obstacles": [[6, 1]]},
+        "rovers": [{"x": 1, "y": 2, "heading": "N"}]}"#;
+    let unknown_type = r#"{"plateau": [5, 5],
+        "rovers": [{"x": 1, "y": 2, "heading": "N", "type": "scout"}]}"#;
+
+    assert_eq!(
+        parse_json_mission(no_plateau),
+        Err(RoverError::InvalidPlateau)
+    );
+    assert_eq!(
+        parse_json_mission(no_rovers),
+        Err(RoverError::InvalidNumberOfCommandsForRover)
+    );
+    assert_eq!(
+        parse_json_mission(missing_heading),
+        Err(RoverError::InvalidJson(
+            "'rovers[0].heading' is missing".to_string()
+        ))
+    );
+    assert_eq!(
+        parse_json_mission(off_plateau),
+        Err(RoverError::InvalidJson(
+            "'options.obstacles[0]' must be a cell on the plateau".to_string()
+        ))
+    );
+    assert_eq!(
+        parse_json_mission(unknown_type),
+        Err(RoverError::InvalidJson(
+            "'rovers[0].type' isn't a declared type".to_string()
+        ))
+    );
+}
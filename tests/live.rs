@@ -0,0 +1,153 @@
+use rusty_rover::live::LiveRover;
+use rusty_rover::rover::{MissionOptions, PositionAndHeading, RoverError, RoverProfile, Topology};
+
+fn position(x: i32, y: i32, heading: char) -> PositionAndHeading {
+    PositionAndHeading { x, y, heading }
+}
+
+fn obstacle_options() -> MissionOptions {
+    MissionOptions {
+        obstacles: vec![(1, 1)],
+        ..MissionOptions::default()
+    }
+}
+
+#[test]
+fn test_live_rover_applies_commands() {
+    let mut rover =
+        LiveRover::new((2, 2), position(0, 0, 'N'), Vec::new(), obstacle_options()).unwrap();
+
+    assert_eq!(rover.apply('m'), Ok(&position(0, 1, 'N')));
+    assert_eq!(rover.apply('R'), Ok(&position(0, 1, 'E')));
+    assert_eq!(rover.state().program, "MR");
+    assert_eq!(rover.render(), "...\n>#.\n*..\n");
+}
+
+#[test]
+fn test_live_rover_rejects_moves() {
+    let mut rover =
+        LiveRover::new((2, 2), position(0, 1, 'E'), Vec::new(), obstacle_options()).unwrap();
+
+    assert_eq!(rover.apply('M'), Err(RoverError::Collision));
+    assert_eq!(rover.apply('L'), Ok(&position(0, 1, 'N')));
+    assert_eq!(rover.apply('M'), Ok(&position(0, 2, 'N')));
+    assert_eq!(rover.apply('M'), Err(RoverError::OutOfBounds));
+    assert_eq!(rover.apply('3'), Err(RoverError::InvalidMove));
+    assert_eq!(rover.apply('X'), Err(RoverError::InvalidMove));
+    assert_eq!(rover.position(), &position(0, 2, 'N'));
+    assert_eq!(rover.state().program, "LM");
+}
+
+#[test]
+fn test_live_rover_keeps_mission_rules() {
+    let options = MissionOptions {
+        rover_profiles: vec![RoverProfile {
+            battery: Some(2),
+            ..RoverProfile::default()
+        }],
+        ..MissionOptions::default()
+    };
+    let mut rover = LiveRover::new((5, 5), position(0, 0, 'N'), Vec::new(), options).unwrap();
+
+    assert!(rover.apply('M').is_ok());
+    assert!(rover.apply('M').is_ok());
+    assert_eq!(rover.apply('M'), Err(RoverError::BatteryDepleted));
+    assert_eq!(
+        LiveRover::new(
+            (5, 5),
+            position(6, 0, 'N'),
+            Vec::new(),
+            MissionOptions::default()
+        ),
+        Err(RoverError::StartOutOfBounds)
+    );
+}
+
+#[test]
+fn test_live_rover_undo_redo() {
+    let mut rover = LiveRover::new(
+        (5, 5),
+        position(0, 0, 'N'),
+        Vec::new(),
+        MissionOptions::default(),
+    )
+    .unwrap();
+    rover.apply('M').unwrap();
+    rover.apply('R').unwrap();
+    rover.apply('M').unwrap();
+
+    assert_eq!(rover.undo(1), 1);
+    assert_eq!(rover.position(), &position(0, 1, 'E'));
+    assert_eq!(rover.undo(5), 2);
+    assert_eq!(rover.undo(1), 0);
+    assert_eq!(rover.position(), &position(0, 0, 'N'));
+    assert_eq!(rover.redo(2), 2);
+    assert_eq!(rover.position(), &position(0, 1, 'E'));
+    assert_eq!(rover.undo(1), 1);
+    assert_eq!(rover.position(), &position(0, 1, 'N'));
+
+    // A new command replaces the ones that could have been redone
+    rover.apply('L').unwrap();
+    assert_eq!(rover.redo(1), 0);
+    assert_eq!(rover.state().program, "ML");
+    assert_eq!(
+        rover.render(),
+        "......\n......\n......\n......\n<.....\n*.....\n"
+    );
+}
+
+#[test]
+fn test_live_rover_stopped_by_parked_rovers() {
+    let parked = vec![position(0, 2, 'S'), position(2, 0, 'W')];
+    let mut rover = LiveRover::new(
+        (2, 2),
+        position(0, 0, 'N'),
+        parked,
+        MissionOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(rover.apply('M'), Ok(&position(0, 1, 'N')));
+    assert_eq!(rover.apply('M'), Err(RoverError::Collision));
+    assert_eq!(rover.position(), &position(0, 1, 'N'));
+    assert_eq!(rover.render(), "v..\n^..\n*.<\n");
+    assert_eq!(
+        LiveRover::new(
+            (2, 2),
+            position(2, 0, 'N'),
+            vec![position(2, 0, 'S')],
+            MissionOptions::default()
+        ),
+        Err(RoverError::Collision)
+    );
+}
+
+#[test]
+fn test_live_rover_turn_keys_and_hex_map() {
+    let diagonal = MissionOptions {
+        rover_profiles: vec![RoverProfile {
+            diagonal: true,
+            ..RoverProfile::default()
+        }],
+        ..MissionOptions::default()
+    };
+    let hex = MissionOptions {
+        topology: Topology::Hex,
+        ..MissionOptions::default()
+    };
+    let diagonal_rover = LiveRover::new((2, 2), position(0, 0, 'N'), Vec::new(), diagonal).unwrap();
+    let hex_rover = LiveRover::new((2, 2), position(0, 0, 'E'), Vec::new(), hex).unwrap();
+
+    assert_eq!(
+        diagonal_rover.turn_keys(),
+        "[L] turn left, [R] turn right, [A] turn 45 degrees left, [C] turn 45 degrees right"
+    );
+    assert_eq!(
+        hex_rover.turn_keys(),
+        "[L] turn 60 degrees left, [R] turn 60 degrees right"
+    );
+    assert_eq!(
+        hex_rover.render(),
+        "Plateau maps are only available on square plateaus.\n"
+    );
+}
@@ -0,0 +1,81 @@
+use rusty_rover::map::{format_map_report, heading_glyph, render_map};
+use rusty_rover::rover::{
+    trace_rover_moves, InputCommand, PositionAndHeading, NORTH_EAST, SOUTH_EAST,
+};
+
+fn example_trajectories() -> Vec<Vec<PositionAndHeading>> {
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            (
+                PositionAndHeading {
+                    x: 1,
+                    y: 2,
+                    heading: 'N',
+                },
+                "LMLMLMLMM".to_string(),
+            ),
+            (
+                PositionAndHeading {
+                    x: 3,
+                    y: 3,
+                    heading: 'E',
+                },
+                "MMRMMRMRRM".to_string(),
+            ),
+        ],
+    };
+    trace_rover_moves(input_command).unwrap()
+}
+
+#[test]
+fn test_heading_glyphs() {
+    assert_eq!(heading_glyph('N'), '^');
+    assert_eq!(heading_glyph('E'), '>');
+    assert_eq!(heading_glyph('S'), 'v');
+    assert_eq!(heading_glyph('W'), '<');
+    assert_eq!(heading_glyph(NORTH_EAST), '/');
+    assert_eq!(heading_glyph(SOUTH_EAST), '\\');
+}
+
+#[test]
+fn test_render_map_draws_rovers_and_obstacles() {
+    let expected_output = "......\n......\n.^....\n..#...\n.....>\n......\n";
+    assert_eq!(
+        render_map((5, 5), &[(2, 2)], &example_trajectories(), false),
+        expected_output
+    );
+}
+
+#[test]
+fn test_render_map_draws_paths() {
+    let expected_output = "......\n......\n.^.***\n**#..*\n**..*>\n......\n";
+    assert_eq!(
+        render_map((5, 5), &[(2, 2)], &example_trajectories(), true),
+        expected_output
+    );
+}
+
+#[test]
+fn test_render_map_leaves_out_cells_off_the_plateau() {
+    let trajectories = vec![vec![PositionAndHeading {
+        x: 3,
+        y: -1,
+        heading: 'S',
+    }]];
+
+    assert_eq!(
+        render_map((1, 1), &[(5, 5)], &trajectories, true),
+        "..\n..\n"
+    );
+}
+
+#[test]
+fn test_map_legend_lists_every_glyph() {
+    let report = format_map_report(".\n");
+    let legend = report.lines().nth(1).unwrap();
+
+    for glyph in ['^', '>', 'v', '<', '/', '\\', '#', '*'] {
+        assert!(legend.contains(&format!("'{}'", glyph)));
+    }
+}
@@ -0,0 +1,473 @@
+use rusty_rover::mission::{parse_macros, parse_mission, simulate_mission, Macro};
+use rusty_rover::rover::{
+    simulate_rover_move, simulate_rovers_with_options, MissionOptions, PositionAndHeading,
+    RoverError, RoverProfile, Topology,
+};
+
+fn mission_lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn test_parse_mission_given_spec() {
+    let test_input = mission_lines(&["5 5", "1 2 N", "LMLMLMLMM", "3 3 E", "MMRMMRMRRM"]);
+
+    let input_command = parse_mission(test_input).unwrap().input_command;
+    assert_eq!(input_command.ur_plateau, (5, 5));
+    assert_eq!(
+        input_command.rovers_to_deploy[1],
+        (
+            PositionAndHeading {
+                x: 3,
+                y: 3,
+                heading: 'E',
+            },
+            "MMRMMRMRRM".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_parse_macros() {
+    let test_input = mission_lines(&["def square = (MMR)4", "5 5", "def TURN=LL"]);
+
+    let expected_output = vec![
+        Macro {
+            name: "SQUARE".to_string(),
+            body: "(MMR)4".to_string(),
+            line: 1,
+        },
+        Macro {
+            name: "TURN".to_string(),
+            body: "LL".to_string(),
+            line: 3,
+        },
+    ];
+    assert_eq!(parse_macros(&test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_mission_with_macros() {
+    let test_input = mission_lines(&[
+        "def SQUARE = (MR)4",
+        "def DOUBLE_SQUARE = (@SQUARE)2",
+        "5 5",
+        "1 2 N",
+        "@double_square M",
+        "3 3 E",
+        "MMR@square",
+    ]);
+
+    let input_command = parse_mission(test_input).unwrap().input_command;
+    assert_eq!(input_command.rovers_to_deploy[0].1, "((((MR)4))2) M");
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 3,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 5,
+            y: 3,
+            heading: 'S',
+        },
+    ];
+    assert_eq!(simulate_rover_move(input_command), Ok(expected_output));
+}
+
+#[test]
+fn test_macro_call_before_counted_command() {
+    let test_input = mission_lines(&["def TURN = R", "5 5", "1 1 N", "@TURN 2M"]);
+
+    // The count belongs to the move after the call, not the call itself
+    let input_command = parse_mission(test_input).unwrap().input_command;
+    let expected_output = vec![PositionAndHeading {
+        x: 3,
+        y: 1,
+        heading: 'E',
+    }];
+    assert_eq!(simulate_rover_move(input_command), Ok(expected_output));
+}
+
+#[test]
+fn test_mission_recursive_macro() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "def LOOP = M@AGAIN",
+        "def AGAIN = L@LOOP",
+        "1 2 N",
+        "@LOOP",
+    ]);
+
+    let expected_output = Err(RoverError::RecursiveMacro {
+        name: "LOOP".to_string(),
+        line: 2,
+    });
+    assert_eq!(
+        parse_mission(test_input).map(|mission| mission.input_command),
+        expected_output
+    );
+}
+
+#[test]
+fn test_mission_invalid_macro_definitions() {
+    let missing_equals = mission_lines(&["5 5", "def SQUARE (MMR)4"]);
+    let bad_body = mission_lines(&["5 5", "def SQUARE = (MMR4"]);
+    let undefined_call = mission_lines(&["def SQUARE = @SIDE R", "5 5"]);
+    let duplicate = mission_lines(&["def SQUARE = M", "def square = L", "5 5"]);
+
+    let invalid_macro = |line| RoverError::InvalidMacro {
+        name: "SQUARE".to_string(),
+        line,
+    };
+    assert_eq!(parse_mission(missing_equals).err(), Some(invalid_macro(2)));
+    assert_eq!(parse_mission(bad_body).err(), Some(invalid_macro(2)));
+    assert_eq!(parse_mission(undefined_call).err(), Some(invalid_macro(1)));
+    assert_eq!(parse_mission(duplicate).err(), Some(invalid_macro(2)));
+}
+
+#[test]
+fn test_mission_undefined_macro() {
+    let test_input = mission_lines(&["5 5", "1 2 N", "MM@SQUARE"]);
+
+    let expected_output = Err(RoverError::UndefinedMacro("SQUARE".to_string()));
+    assert_eq!(
+        parse_mission(test_input).map(|mission| mission.input_command),
+        expected_output
+    );
+}
+
+#[test]
+fn test_parse_mission_obstacles() {
+    let test_input = mission_lines(&["5 5", "obstacle 1 4", "1 2 N", "M", "obstacle 5 0"]);
+
+    let mission = parse_mission(test_input).unwrap();
+    assert_eq!(
+        mission.options,
+        MissionOptions {
+            obstacles: vec![(1, 4), (5, 0)],
+            ..MissionOptions::default()
+        }
+    );
+    assert_eq!(mission.input_command.rovers_to_deploy.len(), 1);
+}
+
+#[test]
+fn test_parse_mission_invalid_obstacles() {
+    let off_plateau = mission_lines(&["5 5", "obstacle 6 1"]);
+    let missing_coordinate = mission_lines(&["5 5", "1 2 N", "M", "obstacle 3"]);
+
+    assert_eq!(
+        parse_mission(off_plateau).err(),
+        Some(RoverError::InvalidObstacle { line: 2 })
+    );
+    assert_eq!(
+        parse_mission(missing_coordinate).err(),
+        Some(RoverError::InvalidObstacle { line: 4 })
+    );
+}
+
+#[test]
+fn test_mission_drive_into_obstacle() {
+    let test_input = mission_lines(&["5 5", "obstacle 1 4", "1 2 N", "MM"]);
+
+    let expected_output = Err(RoverError::Collision);
+    assert_eq!(
+        simulate_mission(parse_mission(test_input).unwrap()),
+        expected_output
+    );
+}
+
+#[test]
+fn test_mission_steer_around_obstacle() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "obstacle 1 4",
+        "1 2 N",
+        "while !edge { if blocked { RMLMMLMR } else { M } }",
+    ]);
+
+    let expected_output = vec![PositionAndHeading {
+        x: 1,
+        y: 5,
+        heading: 'N',
+    }];
+    assert_eq!(
+        simulate_mission(parse_mission(test_input).unwrap()),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_parse_mission_diagonal_rovers() {
+    let test_input = mission_lines(&["5 5", "diagonal 2", "diagonal-turns q e", "1 2 N", "M"]);
+
+    let expected_output = MissionOptions {
+        rover_profiles: vec![
+            RoverProfile {
+                diagonal: false,
+                ..RoverProfile::default()
+            },
+            RoverProfile {
+                diagonal: true,
+                ..RoverProfile::default()
+            },
+        ],
+        diagonal_turns: ('Q', 'E'),
+        ..MissionOptions::default()
+    };
+    assert_eq!(parse_mission(test_input).unwrap().options, expected_output);
+}
+
+#[test]
+fn test_parse_mission_collisions() {
+    let path = mission_lines(&["5 5", "collisions path", "1 2 N", "M"]);
+    let final_position = mission_lines(&["5 5", "collisions final", "1 2 N", "M"]);
+    let invalid = mission_lines(&["5 5", "collisions sometimes", "1 2 N", "M"]);
+
+    assert!(parse_mission(path).unwrap().options.path_collisions);
+    assert!(
+        !parse_mission(final_position)
+            .unwrap()
+            .options
+            .path_collisions
+    );
+    assert_eq!(
+        parse_mission(invalid),
+        Err(RoverError::InvalidOption { line: 2 })
+    );
+}
+
+#[test]
+fn test_parse_mission_invalid_diagonal_options() {
+    let no_rovers = mission_lines(&["5 5", "diagonal"]);
+    let rover_zero = mission_lines(&["5 5", "diagonal 0"]);
+    let clashing_turns = mission_lines(&["5 5", "diagonal-turns L C"]);
+    let same_turns = mission_lines(&["5 5", "diagonal-turns A A"]);
+
+    let invalid_option = Some(RoverError::InvalidOption { line: 2 });
+    assert_eq!(parse_mission(no_rovers).err(), invalid_option);
+    assert_eq!(parse_mission(rover_zero).err(), invalid_option);
+    assert_eq!(parse_mission(clashing_turns).err(), invalid_option);
+    assert_eq!(parse_mission(same_turns).err(), invalid_option);
+}
+
+#[test]
+fn test_mission_diagonal_rover() {
+    let test_input = mission_lines(&["5 5", "diagonal 1", "1 1 NE", "MM C M R", "0 0 N", "CMA"]);
+
+    let expected_output = Err(RoverError::InvalidMove);
+    assert_eq!(
+        simulate_mission(parse_mission(test_input).unwrap()),
+        expected_output
+    );
+
+    let test_input = mission_lines(&["5 5", "diagonal 1", "1 1 NE", "MM C M R"]);
+    let expected_output = vec![PositionAndHeading {
+        x: 4,
+        y: 3,
+        heading: 'S',
+    }];
+    assert_eq!(
+        simulate_mission(parse_mission(test_input).unwrap()),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_parse_mission_topology() {
+    let hex = mission_lines(&["topology hex", "3 3", "0 0 NE", "M"]);
+    let square = mission_lines(&["topology square", "3 3", "0 0 N", "M"]);
+    let unknown = mission_lines(&["3 3", "topology triangle", "0 0 N", "M"]);
+
+    assert_eq!(parse_mission(hex).unwrap().options.topology, Topology::Hex);
+    assert_eq!(
+        parse_mission(square).unwrap().options.topology,
+        Topology::Square
+    );
+    assert_eq!(
+        parse_mission(unknown).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+}
+
+#[test]
+fn test_parse_mission_rover_types() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "rover 1 scout",
+        "type scout speed=2 turning=45 commands=lrmac battery=10",
+        "type heavy commands=LRM",
+        "rover 3 HEAVY",
+        "diagonal 3",
+        "1 2 N",
+        "M",
+    ]);
+
+    let expected_output = MissionOptions {
+        rover_profiles: vec![
+            RoverProfile {
+                diagonal: true,
+                speed: 2,
+                allowed_commands: Some(vec!['L', 'R', 'M', 'A', 'C']),
+                battery: Some(10),
+                ..RoverProfile::default()
+            },
+            RoverProfile::default(),
+            RoverProfile {
+                diagonal: true,
+                allowed_commands: Some(vec!['L', 'R', 'M']),
+                ..RoverProfile::default()
+            },
+        ],
+        ..MissionOptions::default()
+    };
+    assert_eq!(parse_mission(test_input).unwrap().options, expected_output);
+}
+
+#[test]
+fn test_parse_mission_invalid_rover_types() {
+    let unknown_type = mission_lines(&["5 5", "rover 1 scout"]);
+    let unknown_capability = mission_lines(&["5 5", "type scout wheels=6"]);
+    let zero_speed = mission_lines(&["5 5", "type scout speed=0"]);
+    let duplicate_type = mission_lines(&["5 5", "type scout", "type SCOUT speed=2"]);
+    let bad_footprint = mission_lines(&["5 5", "type heavy footprint=2"]);
+    let diagonal_footprint = mission_lines(&["5 5", "type heavy footprint=2x2 turning=45"]);
+
+    assert_eq!(
+        parse_mission(unknown_type).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+    assert_eq!(
+        parse_mission(unknown_capability).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+    assert_eq!(
+        parse_mission(zero_speed).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+    assert_eq!(
+        parse_mission(bad_footprint).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+    assert_eq!(
+        parse_mission(diagonal_footprint).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+    assert_eq!(
+        parse_mission(duplicate_type).err(),
+        Some(RoverError::InvalidOption { line: 3 })
+    );
+}
+
+#[test]
+fn test_mission_rover_capabilities() {
+    let fast = mission_lines(&["5 5", "type fast speed=2", "rover 1 fast", "0 0 N", "MRM"]);
+    let fast_into_obstacle = mission_lines(&[
+        "5 5",
+        "obstacle 0 1",
+        "type fast speed=2",
+        "rover 1 fast",
+        "0 0 N",
+        "M",
+    ]);
+    let flat_battery = mission_lines(&[
+        "5 5",
+        "type small battery=3",
+        "rover 1 small",
+        "0 0 N",
+        "MRMM",
+    ]);
+    // Samples don't use the battery, so this one has just enough
+    let sampling_battery = mission_lines(&[
+        "5 5",
+        "type small battery=3",
+        "rover 1 small",
+        "0 0 N",
+        "SMSRSM",
+    ]);
+    let large = mission_lines(&["5 5", "type big footprint=2x2", "rover 1 big", "5 0 N", "M"]);
+    let rail = mission_lines(&["5 5", "type rail commands=M", "rover 1 rail", "0 0 N", "ML"]);
+
+    let expected_output = vec![PositionAndHeading {
+        x: 2,
+        y: 2,
+        heading: 'E',
+    }];
+    assert_eq!(
+        simulate_mission(parse_mission(fast).unwrap()),
+        Ok(expected_output)
+    );
+    assert_eq!(
+        simulate_mission(parse_mission(fast_into_obstacle).unwrap()),
+        Err(RoverError::Collision)
+    );
+    assert_eq!(
+        simulate_mission(parse_mission(flat_battery).unwrap()),
+        Err(RoverError::BatteryDepleted)
+    );
+    assert_eq!(
+        simulate_mission(parse_mission(sampling_battery).unwrap()),
+        Ok(vec![PositionAndHeading {
+            x: 1,
+            y: 1,
+            heading: 'E',
+        }])
+    );
+    assert_eq!(
+        simulate_mission(parse_mission(large).unwrap()),
+        Err(RoverError::StartOutOfBounds)
+    );
+    assert_eq!(
+        simulate_mission(parse_mission(rail).unwrap()),
+        Err(RoverError::InvalidMove)
+    );
+}
+
+#[test]
+fn test_mission_sample_collection() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "sample 1 3",
+        "sample 2 2",
+        "1 2 N",
+        "SMS",
+        "2 1 N",
+        "MSSM",
+        "3 2 W",
+        "MS",
+    ]);
+
+    let mission = parse_mission(test_input).unwrap();
+    assert_eq!(mission.options.sample_sites, vec![(1, 3), (2, 2)]);
+    let samples = simulate_rovers_with_options(mission.input_command, &mission.options)
+        .unwrap()
+        .into_iter()
+        .map(|report| report.samples)
+        .collect::<Vec<_>>();
+    assert_eq!(samples, vec![vec![(1, 3)], vec![(2, 2)], vec![]]);
+}
+
+#[test]
+fn test_mission_sample_payload() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "sample 0 1",
+        "sample 0 2",
+        "type mule payload=1",
+        "rover 1 mule",
+        "0 0 N",
+        "MSMS",
+    ]);
+    let off_plateau = mission_lines(&["5 5", "sample 6 1", "0 0 N", "S"]);
+
+    assert_eq!(
+        simulate_mission(parse_mission(test_input).unwrap()),
+        Err(RoverError::PayloadFull)
+    );
+    assert_eq!(
+        parse_mission(off_plateau).err(),
+        Some(RoverError::InvalidOption { line: 2 })
+    );
+}
@@ -0,0 +1,164 @@
+use rusty_rover::check::Outcome;
+use rusty_rover::mission::parse_mission;
+use rusty_rover::mission_log::{
+    format_events, mission_events, parse_event, replay, verify_log, MissionEvent,
+};
+use rusty_rover::rover::{
+    simulate_rovers_until_failure, MissionOptions, PositionAndHeading, RoverError,
+};
+
+fn mission_log(lines: &[&str]) -> String {
+    let mission = parse_mission(lines.iter().map(|line| line.to_string()).collect()).unwrap();
+    let result = simulate_rovers_until_failure(mission.input_command, &mission.options).unwrap();
+    format_events(&mission_events(&result))
+}
+
+fn position(x: i32, y: i32, heading: char) -> PositionAndHeading {
+    PositionAndHeading { x, y, heading }
+}
+
+#[test]
+fn test_mission_events() {
+    let log = mission_log(&["2 2", "0 0 N", "MR", "2 2 S", "MMM"]);
+
+    let expected_output = concat!(
+        r#"{"event":"deployed","rover":1,"x":0,"y":0,"heading":"N"}"#,
+        "\n",
+        r#"{"event":"moved","rover":1,"command":"M","x":0,"y":1,"heading":"N"}"#,
+        "\n",
+        r#"{"event":"turned","rover":1,"command":"R","x":0,"y":1,"heading":"E"}"#,
+        "\n",
+        r#"{"event":"finished","rover":1,"x":0,"y":1,"heading":"E"}"#,
+        "\n",
+        r#"{"event":"deployed","rover":2,"x":2,"y":2,"heading":"S"}"#,
+        "\n",
+        r#"{"event":"moved","rover":2,"command":"M","x":2,"y":1,"heading":"S"}"#,
+        "\n",
+        r#"{"event":"moved","rover":2,"command":"M","x":2,"y":0,"heading":"S"}"#,
+        "\n",
+        r#"{"event":"blocked","rover":2,"x":2,"y":0,"heading":"S","error":"OutOfBounds"}"#,
+        "\n",
+    );
+    assert_eq!(log, expected_output);
+    assert_eq!(
+        parse_event(log.lines().nth(2).unwrap()),
+        Ok(MissionEvent::Turned {
+            rover: 0,
+            command: 'R',
+            position: position(0, 1, 'E'),
+        })
+    );
+}
+
+#[test]
+fn test_replay_uses_last_run() {
+    let first_run = mission_log(&["2 2", "0 0 N", "M"]);
+    let second_run = mission_log(&["2 2", "0 0 N", "MR", "2 2 S", "MMM"]);
+
+    let expected_output = vec![
+        Outcome::Finished(position(0, 1, 'E')),
+        Outcome::Failed("OutOfBounds".to_string()),
+    ];
+    assert_eq!(
+        replay(
+            &format!("{}{}", first_run, second_run),
+            &MissionOptions::default()
+        ),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_verify_log_against_fresh_run() {
+    let lines = ["5 5", "1 2 N", "LMLMLMLMM", "3 3 E", "MMRMMRMRRM"];
+    let mission = parse_mission(lines.iter().map(|line| line.to_string()).collect()).unwrap();
+    let log = mission_log(&lines);
+
+    assert_eq!(verify_log(&log, &mission), Ok(true));
+
+    // Editing where a rover finished no longer matches the mission
+    let edited = log.replace(
+        r#"{"event":"finished","rover":2,"x":5,"y":1"#,
+        r#"{"event":"finished","rover":2,"x":4,"y":1"#,
+    );
+    assert!(replay(&edited, &mission.options).is_err());
+    let shortened = log.lines().take(11).collect::<Vec<_>>().join("\n");
+    assert_eq!(verify_log(&shortened, &mission), Ok(false));
+}
+
+#[test]
+fn test_replay_rejects_broken_logs() {
+    let log = mission_log(&["2 2", "0 0 N", "MR"]);
+    let lines = log.lines().collect::<Vec<_>>();
+    let options = MissionOptions::default();
+
+    assert_eq!(
+        replay(&lines[..2].join("\n"), &options),
+        Err(RoverError::InvalidJson(
+            "line 2: the log ends before rover 1 finished".to_string()
+        ))
+    );
+    assert_eq!(
+        replay(&[lines[0], lines[3]].join("\n"), &options),
+        Err(RoverError::InvalidJson(
+            "line 2: the event is out of order".to_string()
+        ))
+    );
+    assert_eq!(
+        replay(
+            r#"{"event":"deployed","rover":1,"x":0,"heading":"N"}"#,
+            &options
+        ),
+        Err(RoverError::InvalidJson(
+            "line 1: 'y' is missing".to_string()
+        ))
+    );
+
+    // Each step has to be one its command could make
+    let jumped = log.replace(
+        r#""x":0,"y":1,"heading":"N""#,
+        r#""x":1,"y":1,"heading":"N""#,
+    );
+    assert_eq!(
+        replay(&jumped, &options),
+        Err(RoverError::InvalidJson(
+            "line 2: rover 1 can't make that step with 'M'".to_string()
+        ))
+    );
+    let relabeled = log.replace(r#""command":"R""#, r#""command":"L""#);
+    assert_eq!(
+        replay(&relabeled, &options),
+        Err(RoverError::InvalidJson(
+            "line 3: rover 1 can't make that step with 'L'".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_samples_and_fast_moves_are_logged() {
+    let lines = [
+        "type FAST speed=2",
+        "rover 1 FAST",
+        "sample 0 2",
+        "3 3",
+        "0 0 N",
+        "MS",
+    ];
+    let mission = parse_mission(lines.iter().map(|line| line.to_string()).collect()).unwrap();
+    let log = mission_log(&lines);
+
+    let expected_output = concat!(
+        r#"{"event":"deployed","rover":1,"x":0,"y":0,"heading":"N"}"#,
+        "\n",
+        r#"{"event":"moved","rover":1,"command":"M","x":0,"y":1,"heading":"N"}"#,
+        "\n",
+        r#"{"event":"moved","rover":1,"command":"M","x":0,"y":2,"heading":"N"}"#,
+        "\n",
+        r#"{"event":"stayed","rover":1,"command":"S","x":0,"y":2,"heading":"N"}"#,
+        "\n",
+        r#"{"event":"finished","rover":1,"x":0,"y":2,"heading":"N"}"#,
+        "\n",
+    );
+    assert_eq!(log, expected_output);
+    assert_eq!(verify_log(&log, &mission), Ok(true));
+}
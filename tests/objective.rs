@@ -0,0 +1,123 @@
+use rusty_rover::mission::parse_mission;
+use rusty_rover::objective::{format_score_report, score_mission, score_objectives, Objective};
+use rusty_rover::rover::{simulate_rovers_until_failure, simulate_rovers_with_options, RoverError};
+
+fn mission_lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn test_parse_mission_objectives() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "objective 1 reach 1 3",
+        "objective 2 heading e",
+        "objective 2 waypoints 5 3 5 1",
+        "objective 1 samples 2",
+        "1 2 N",
+        "LMLMLMLMM",
+        "3 3 E",
+        "MMRMMRMRRM",
+    ]);
+
+    let expected_output = vec![
+        Objective::Reach {
+            rover: 0,
+            cell: (1, 3),
+        },
+        Objective::Heading {
+            rover: 1,
+            heading: 'E',
+        },
+        Objective::Waypoints {
+            rover: 1,
+            cells: vec![(5, 3), (5, 1)],
+        },
+        Objective::Samples { rover: 0, count: 2 },
+    ];
+    assert_eq!(
+        parse_mission(test_input).unwrap().objectives,
+        expected_output
+    );
+}
+
+#[test]
+fn test_parse_mission_invalid_objectives() {
+    let unknown_rover = mission_lines(&["5 5", "objective 2 reach 1 3", "1 2 N", "M"]);
+    let off_plateau = mission_lines(&["5 5", "objective 1 reach 6 3", "1 2 N", "M"]);
+    let odd_waypoints = mission_lines(&["5 5", "objective 1 waypoints 1 2 3", "1 2 N", "M"]);
+    let unknown_objective = mission_lines(&["5 5", "objective 1 explore", "1 2 N", "M"]);
+
+    let invalid_option = Some(RoverError::InvalidOption { line: 2 });
+    assert_eq!(parse_mission(unknown_rover).err(), invalid_option);
+    assert_eq!(parse_mission(off_plateau).err(), invalid_option);
+    assert_eq!(parse_mission(odd_waypoints).err(), invalid_option);
+    assert_eq!(parse_mission(unknown_objective).err(), invalid_option);
+}
+
+#[test]
+fn test_score_objectives() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "sample 1 3",
+        "objective 1 reach 1 3",
+        "objective 1 samples 1",
+        "objective 2 heading N",
+        "objective 2 waypoints 5 3 5 1",
+        "objective 2 waypoints 5 1 5 3",
+        "1 2 N",
+        "MS",
+        "3 3 E",
+        "MMRMMRMRRM",
+    ]);
+
+    let mission = parse_mission(test_input).unwrap();
+    let reports = simulate_rovers_with_options(mission.input_command, &mission.options).unwrap();
+    let score_report = score_objectives(&mission.objectives, &reports);
+    let satisfied = score_report
+        .results
+        .iter()
+        .map(|result| result.satisfied)
+        .collect::<Vec<_>>();
+    assert_eq!(satisfied, vec![true, true, false, true, false]);
+    assert_eq!(score_report.score, 3);
+    assert!(!score_report.passed);
+}
+
+#[test]
+fn test_rover_error_misses_every_objective() {
+    let test_input = mission_lines(&[
+        "5 5",
+        "objective 1 reach 1 3",
+        "1 2 N",
+        "M",
+        "3 3 E",
+        "MMMMMM",
+    ]);
+
+    // The first rover reaches its cell, but the second one drives off the plateau
+    let mission = parse_mission(test_input).unwrap();
+    let result = simulate_rovers_until_failure(mission.input_command, &mission.options).unwrap();
+    assert!(score_objectives(&mission.objectives, &result.reports).passed);
+    let score_report = score_mission(&mission.objectives, &result);
+    assert_eq!(score_report.score, 0);
+    assert!(!score_report.passed);
+}
+
+#[test]
+fn test_format_score_report() {
+    let objectives = vec![
+        Objective::Reach {
+            rover: 0,
+            cell: (1, 3),
+        },
+        Objective::Samples { rover: 1, count: 2 },
+    ];
+
+    // Without any rover reports, as when the simulation fails, nothing is satisfied
+    let score_report = score_objectives(&objectives, &[]);
+    assert_eq!(
+        format_score_report(&score_report),
+        "[FAIL] Rover 1 finishes on 1 3\n[FAIL] Rover 2 collects 2 sample(s)\n\nScore: 0/2 (FAIL)\n"
+    );
+}
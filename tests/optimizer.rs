@@ -0,0 +1,140 @@
+use rusty_rover::optimizer::{
+    optimize_program, optimize_program_with_topology, optimize_rover_program,
+};
+use rusty_rover::rover::{
+    simulate_rover_move, simulate_rover_move_with_options, InputCommand, MissionOptions,
+    PositionAndHeading, RoverError, RoverProfile, Topology,
+};
+
+#[test]
+fn test_optimize_redundant_turns() {
+    assert_eq!(optimize_program("LLLL"), Ok("".to_string()));
+    assert_eq!(optimize_program("LR"), Ok("".to_string()));
+    assert_eq!(optimize_program("RRR"), Ok("L".to_string()));
+    assert_eq!(optimize_program("LLL"), Ok("R".to_string()));
+    assert_eq!(optimize_program("RLRR"), Ok("RR".to_string()));
+    assert_eq!(optimize_program("LLLLLL"), Ok("LL".to_string()));
+}
+
+#[test]
+fn test_optimize_keeps_every_move() {
+    assert_eq!(optimize_program("MRRRRMLRLMRRRM"), Ok("MMLMLM".to_string()));
+    assert_eq!(optimize_program("LMLMLMLMM"), Ok("LMLMLMLMM".to_string()));
+}
+
+#[test]
+fn test_optimize_normalizes_input() {
+    assert_eq!(optimize_program("l m r r r m"), Ok("LMLM".to_string()));
+}
+
+#[test]
+fn test_optimize_invalid_move() {
+    assert_eq!(optimize_program("LABC"), Err(RoverError::InvalidMove));
+}
+
+#[test]
+fn test_optimized_program_same_final_position() {
+    let program = "MMRRRRMRLLLRRMMRRRRM";
+    let rover_with_program = |program: String| InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 1,
+                heading: 'N',
+            },
+            program,
+        )],
+    };
+
+    let optimized_program = optimize_program(program).unwrap();
+    assert!(optimized_program.len() < program.len());
+    assert_eq!(
+        simulate_rover_move(rover_with_program(program.to_string())),
+        simulate_rover_move(rover_with_program(optimized_program))
+    );
+}
+
+#[test]
+fn test_optimize_expands_repetition() {
+    assert_eq!(optimize_program("4L 2M 3R"), Ok("MML".to_string()));
+    assert_eq!(optimize_program("(MRR)2"), Ok("MRRMRR".to_string()));
+}
+
+#[test]
+fn test_optimize_reverse_and_u_turn() {
+    assert_eq!(optimize_program("UUMB"), Ok("MB".to_string()));
+    assert_eq!(optimize_program("RUBLU"), Ok("LBR".to_string()));
+}
+
+#[test]
+fn test_optimize_keeps_samples() {
+    assert_eq!(optimize_program("LSRM"), Ok("LSRM".to_string()));
+    assert_eq!(optimize_program("RSRMS"), Ok("RSRMS".to_string()));
+    assert_eq!(optimize_program("LLLLSRRRM"), Ok("SLM".to_string()));
+}
+
+#[test]
+fn test_optimize_hex_turns() {
+    let hex_options = MissionOptions {
+        topology: Topology::Hex,
+        ..MissionOptions::default()
+    };
+    let rover_with_program = |program: String| InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 1,
+                heading: 'E',
+            },
+            program,
+        )],
+    };
+
+    assert_eq!(
+        optimize_program_with_topology("LLLM", Topology::Hex),
+        Ok("LLLM".to_string())
+    );
+    assert_eq!(
+        optimize_program_with_topology("RRRRRM", Topology::Hex),
+        Ok("LM".to_string())
+    );
+    assert_eq!(
+        optimize_program_with_topology("UMRRRR", Topology::Hex),
+        Ok("LLLMLL".to_string())
+    );
+    for program in ["LLLM", "RRRRRM", "UMRRRR", "LRLLLLMUR"] {
+        let optimized_program = optimize_program_with_topology(program, Topology::Hex).unwrap();
+        assert_eq!(
+            simulate_rover_move_with_options(rover_with_program(program.to_string()), &hex_options),
+            simulate_rover_move_with_options(rover_with_program(optimized_program), &hex_options)
+        );
+    }
+}
+
+#[test]
+fn test_optimize_leaves_battery_and_large_rovers_alone() {
+    let options = MissionOptions {
+        rover_profiles: vec![
+            RoverProfile::default(),
+            RoverProfile {
+                battery: Some(2),
+                ..RoverProfile::default()
+            },
+            RoverProfile {
+                footprint: (2, 2),
+                ..RoverProfile::default()
+            },
+        ],
+        ..MissionOptions::default()
+    };
+
+    // The second rover runs flat on LLLLM, which optimized to M would let it finish
+    assert_eq!(
+        optimize_rover_program("LLLLM", &options, 0),
+        Some("M".to_string())
+    );
+    assert_eq!(optimize_rover_program("LLLLM", &options, 1), None);
+    assert_eq!(optimize_rover_program("LLLLM", &options, 2), None);
+}
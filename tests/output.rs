@@ -0,0 +1,104 @@
+use rusty_rover::check::{rover_outcomes, Outcome};
+use rusty_rover::mission::parse_mission;
+use rusty_rover::output::{
+    get_formatter, CsvFormatter, JsonFormatter, OutputFormatter, TextFormatter,
+};
+use rusty_rover::rover::{PositionAndHeading, RoverError};
+
+fn mission_outcomes(lines: &[&str]) -> Vec<Outcome> {
+    let mission = parse_mission(lines.iter().map(|line| line.to_string()).collect()).unwrap();
+    rover_outcomes(&mission).unwrap()
+}
+
+fn failed_mission_outcomes() -> Vec<Outcome> {
+    mission_outcomes(&["5 5", "1 2 N", "LMLMLMLMM", "3 3 E", "MMMMMM", "0 0 N", "M"])
+}
+
+#[test]
+fn test_rover_outcomes_of_successful_mission() {
+    let outcomes = mission_outcomes(&["5 5", "1 2 N", "LMLMLMLMM", "3 3 E", "MMRMMRMRRM"]);
+
+    let expected_output = vec![
+        Outcome::Finished(PositionAndHeading {
+            x: 1,
+            y: 3,
+            heading: 'N',
+        }),
+        Outcome::Finished(PositionAndHeading {
+            x: 5,
+            y: 1,
+            heading: 'E',
+        }),
+    ];
+    assert_eq!(outcomes, expected_output);
+}
+
+#[test]
+fn test_text_output() {
+    let expected_output = "1 3 N\nerror OutOfBounds\nnot deployed\n";
+    assert_eq!(
+        TextFormatter.format(&failed_mission_outcomes()),
+        expected_output
+    );
+}
+
+#[test]
+fn test_json_output() {
+    let expected_output = concat!(
+        r#"[{"index":1,"x":1,"y":3,"heading":"N","status":"ok"},"#,
+        r#"{"index":2,"x":null,"y":null,"heading":null,"status":"OutOfBounds"},"#,
+        r#"{"index":3,"x":null,"y":null,"heading":null,"status":"not deployed"}]"#,
+        "\n"
+    );
+    assert_eq!(
+        JsonFormatter.format(&failed_mission_outcomes()),
+        expected_output
+    );
+}
+
+#[test]
+fn test_csv_output() {
+    let expected_output =
+        "index,x,y,heading,status\n1,1,3,N,ok\n2,,,,OutOfBounds\n3,,,,not deployed\n";
+    assert_eq!(
+        CsvFormatter.format(&failed_mission_outcomes()),
+        expected_output
+    );
+}
+
+#[test]
+fn test_format_results_and_errors() {
+    let outcomes = failed_mission_outcomes();
+
+    assert_eq!(
+        TextFormatter.format_results(&outcomes),
+        "\nFinal rover position(s):\n\n1 3 N\nerror OutOfBounds\nnot deployed\n"
+    );
+    assert_eq!(
+        CsvFormatter.format_results(&outcomes),
+        CsvFormatter.format(&outcomes)
+    );
+    assert_eq!(
+        TextFormatter.format_error(&RoverError::InvalidObstacle { line: 3 }),
+        "Error: InvalidObstacle { line: 3 }. Please check your obstacle(s).\n"
+    );
+    assert_eq!(
+        JsonFormatter.format_error(&RoverError::InvalidPlateau),
+        "{\"error\":{\"name\":\"InvalidPlateau\",\"detail\":\"the plateau coordinates are invalid\"}}\n"
+    );
+    assert_eq!(
+        CsvFormatter.format_error(&RoverError::InvalidOption { line: 2 }),
+        "error,detail\nInvalidOption,\"the option on line 2 is invalid\"\n"
+    );
+}
+
+#[test]
+fn test_get_formatter() {
+    let outcomes = failed_mission_outcomes();
+
+    assert_eq!(
+        get_formatter("csv").unwrap().format(&outcomes),
+        CsvFormatter.format(&outcomes)
+    );
+    assert!(get_formatter("xml").is_none());
+}
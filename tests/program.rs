@@ -0,0 +1,114 @@
+use rusty_rover::program::{
+    expand_program, parse_program, program_commands, Condition, Instruction, MAX_PROGRAM_LENGTH,
+};
+use rusty_rover::rover::RoverError;
+
+#[test]
+fn test_expand_plain_program() {
+    assert_eq!(expand_program("LMLMLMLMM"), Ok("LMLMLMLMM".to_string()));
+    assert_eq!(expand_program("L M  LM"), Ok("LMLM".to_string()));
+}
+
+#[test]
+fn test_expand_counts() {
+    assert_eq!(expand_program("16M"), Ok("M".repeat(16)));
+    assert_eq!(expand_program("3LM"), Ok("LLLM".to_string()));
+    assert_eq!(expand_program("0MR"), Ok("R".to_string()));
+}
+
+#[test]
+fn test_expand_groups() {
+    assert_eq!(expand_program("(MMR)4"), Ok("MMR".repeat(4)));
+    assert_eq!(expand_program("(MR)"), Ok("MR".to_string()));
+    assert_eq!(expand_program("2(ML)"), Ok("MLML".to_string()));
+    assert_eq!(expand_program("(2M(L)2)2 M"), Ok("MMLLMMLLM".to_string()));
+
+    // Only a count straight after the bracket repeats the group, so a spaced one
+    // counts the instruction after it
+    assert_eq!(expand_program("(MR) 2M"), Ok("MRMM".to_string()));
+    assert_eq!(expand_program("(MR)2M"), Ok("MRMRM".to_string()));
+}
+
+#[test]
+fn test_expand_ignores_non_command_chars() {
+    assert_eq!(expand_program("(M%M)2'L"), Ok("MMMML".to_string()));
+}
+
+#[test]
+fn test_expand_invalid_syntax() {
+    assert_eq!(expand_program("(MM"), Err(RoverError::InvalidMove));
+    assert_eq!(expand_program("MM)"), Err(RoverError::InvalidMove));
+    assert_eq!(expand_program("4"), Err(RoverError::InvalidMove));
+    assert_eq!(expand_program("4()"), Ok("".to_string()));
+    assert_eq!(expand_program("M{M}"), Err(RoverError::InvalidMove));
+    assert_eq!(expand_program("2(ML)3"), Err(RoverError::InvalidMove));
+}
+
+#[test]
+fn test_expand_too_long() {
+    assert_eq!(
+        expand_program(&format!("{}M", MAX_PROGRAM_LENGTH + 1)),
+        Err(RoverError::ProgramTooLong)
+    );
+    assert_eq!(
+        expand_program("(((M)1000)1000)1000"),
+        Err(RoverError::ProgramTooLong)
+    );
+    assert_eq!(
+        expand_program("(M)99999999999999999999999"),
+        Err(RoverError::ProgramTooLong)
+    );
+    assert_eq!(
+        expand_program(&format!("{}M{}", "(".repeat(100), ")".repeat(100))),
+        Err(RoverError::ProgramTooLong)
+    );
+    assert_eq!(
+        parse_program(&format!("while {}edge {{ M }}", "!".repeat(100))),
+        Err(RoverError::ProgramTooLong)
+    );
+}
+
+#[test]
+fn test_parse_program_conditions() {
+    let expected_output = vec![
+        Instruction::Command('M'),
+        Instruction::If(
+            Condition::Blocked,
+            vec![Instruction::Command('R')],
+            vec![Instruction::Repeat(2, vec![Instruction::Command('M')])],
+        ),
+        Instruction::While(
+            Condition::Not(Box::new(Condition::Edge)),
+            vec![Instruction::Command('M')],
+        ),
+    ];
+    assert_eq!(
+        parse_program("M if blocked { R } else { 2M } while !edge { M }"),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_parse_program_invalid_conditions() {
+    assert_eq!(parse_program("if { M }"), Err(RoverError::InvalidMove));
+    assert_eq!(parse_program("if blocked M"), Err(RoverError::InvalidMove));
+    assert_eq!(
+        parse_program("while edge { M"),
+        Err(RoverError::InvalidMove)
+    );
+    assert_eq!(parse_program("else { M }"), Err(RoverError::InvalidMove));
+}
+
+#[test]
+fn test_conditional_program_does_not_expand() {
+    assert_eq!(
+        expand_program("while !edge { M }"),
+        Err(RoverError::InvalidMove)
+    );
+}
+
+#[test]
+fn test_program_commands() {
+    let program = parse_program("L if edge { R } else { (MX)3 }").unwrap();
+    assert_eq!(program_commands(&program), "LRMX");
+}
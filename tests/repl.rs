@@ -0,0 +1,137 @@
+use rusty_rover::repl::{Repl, ReplReply, REPL_HELP};
+
+fn output(repl: &mut Repl, line: &str) -> String {
+    match repl.handle_line(line) {
+        ReplReply::Output(output) => output,
+        ReplReply::Quit => panic!("unexpected quit"),
+    }
+}
+
+#[test]
+fn test_repl_runs_mission() {
+    let mut repl = Repl::new();
+    output(&mut repl, ":plateau 5 5");
+    output(&mut repl, ":rover 1 2 N LMLMLMLMM");
+    output(&mut repl, ":rover 3 3 e mmrmmrmrrm");
+
+    assert_eq!(
+        output(&mut repl, ":list"),
+        "Plateau: 5 5\nRover 1: 1 2 N LMLMLMLMM\nRover 2: 3 3 E mmrmmrmrrm\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":run"),
+        "Final rover position(s):\n\n1 3 N\n5 1 E\n"
+    );
+}
+
+#[test]
+fn test_repl_keeps_state_between_runs() {
+    let mut repl = Repl::new();
+    output(&mut repl, ":plateau 5 5");
+    output(&mut repl, ":rover 3 3 E MMMMMM");
+    output(&mut repl, ":rover 0 0 N while !edge { M }");
+
+    assert_eq!(
+        output(&mut repl, ":run"),
+        "Final rover position(s):\n\nerror OutOfBounds\nnot deployed\n"
+    );
+    assert_eq!(output(&mut repl, ":delete 1"), "Deleted rover 1\n");
+    assert_eq!(
+        output(&mut repl, ":run"),
+        "Final rover position(s):\n\n0 5 N\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":reset"),
+        "Cleared the plateau, rovers and options\n"
+    );
+    assert_eq!(output(&mut repl, ":list"), "Plateau: not set\nNo rovers\n");
+}
+
+#[test]
+fn test_repl_errors() {
+    let mut repl = Repl::new();
+
+    assert_eq!(
+        output(&mut repl, ":run"),
+        "Error: no plateau. Please set one with ':plateau X Y'.\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":plateau 5"),
+        "Error: InvalidPlateau. Please use ':plateau X Y', with X and Y from 0 to 9.\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":plateau 10 10"),
+        "Error: InvalidPlateau. Please use ':plateau X Y', with X and Y from 0 to 9.\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":rover 1 N"),
+        "Error: InvalidStartPosition. Please use ':rover X Y HEADING PROGRAM', with X and Y from 0 to 9.\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":rover 12 1 N M"),
+        "Error: InvalidStartPosition. Please use ':rover X Y HEADING PROGRAM', with X and Y from 0 to 9.\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":delete 1"),
+        "Error: no rover '1'. Please check ':list'.\n"
+    );
+    assert_eq!(
+        output(&mut repl, "1 2 N"),
+        "Error: unknown command '1 2 N'. Type ':help' for the commands.\n"
+    );
+    assert_eq!(output(&mut repl, ":help"), REPL_HELP);
+    assert_eq!(repl.handle_line(":quit"), ReplReply::Quit);
+}
+
+#[test]
+fn test_repl_undo_redo() {
+    let mut repl = Repl::new();
+    output(&mut repl, ":plateau 5 5");
+    output(&mut repl, ":rover 1 2 N M");
+    output(&mut repl, ":rover 3 3 E M");
+    output(&mut repl, ":delete 1");
+
+    assert_eq!(output(&mut repl, ":undo"), "Undid 1 change(s)\n");
+    assert_eq!(repl.mission().rovers_to_deploy.len(), 2);
+    assert_eq!(output(&mut repl, ":undo 5"), "Undid 3 change(s)\n");
+    assert_eq!(output(&mut repl, ":undo"), "Nothing to undo\n");
+    assert_eq!(output(&mut repl, ":list"), "Plateau: not set\nNo rovers\n");
+    assert_eq!(output(&mut repl, ":redo 2"), "Redid 2 change(s)\n");
+    assert_eq!(
+        output(&mut repl, ":list"),
+        "Plateau: 5 5\nRover 1: 1 2 N M\n"
+    );
+    assert_eq!(output(&mut repl, ":redo x"), "Error: invalid count 'x'.\n");
+}
+
+#[test]
+fn test_repl_options() {
+    let mut repl = Repl::new();
+    output(&mut repl, ":plateau 5 5");
+    output(&mut repl, ":rover 1 1 N MM");
+
+    assert_eq!(
+        output(&mut repl, ":option obstacle 1 2"),
+        "Added option 1\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":list"),
+        "Plateau: 5 5\nOption: obstacle 1 2\nRover 1: 1 1 N MM\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":run"),
+        "Final rover position(s):\n\nerror Collision\n"
+    );
+    assert_eq!(
+        output(&mut repl, ":option teleport 1 2"),
+        "Error: InvalidOption. Please use ':option DIRECTIVE', e.g. ':option obstacle 2 2'.\n"
+    );
+
+    // Directives are checked against the plateau when the mission is run
+    output(&mut repl, ":undo");
+    output(&mut repl, ":option obstacle 9 9");
+    assert_eq!(
+        output(&mut repl, ":run"),
+        "Error: InvalidObstacle { line: 1 }. Please check your obstacle(s).\n"
+    );
+}
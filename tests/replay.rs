@@ -0,0 +1,45 @@
+use rusty_rover::replay::{format_frame, replay_frames};
+use rusty_rover::rover::{
+    simulate_rovers_until_failure, InputCommand, MissionOptions, PositionAndHeading,
+};
+
+fn rover_at(x: i32, y: i32, heading: char, program: &str) -> (PositionAndHeading, String) {
+    (PositionAndHeading { x, y, heading }, program.to_string())
+}
+
+fn frames_for(rovers_to_deploy: Vec<(PositionAndHeading, String)>) -> Vec<String> {
+    let input_command = InputCommand {
+        ur_plateau: (2, 2),
+        rovers_to_deploy,
+    };
+    let result = simulate_rovers_until_failure(input_command, &MissionOptions::default()).unwrap();
+    replay_frames(&result)
+        .iter()
+        .map(|frame| format_frame((2, 2), &[(1, 1)], frame))
+        .collect()
+}
+
+#[test]
+fn test_replay_frames_follow_each_rover() {
+    let frames = frames_for(vec![rover_at(0, 0, 'N', "MR"), rover_at(2, 2, 'S', "M")]);
+
+    let expected_output = vec![
+        "Rover 1, step 0/2\n\n...\n.#.\n^..\n",
+        "Rover 1, step 1/2\n\n...\n^#.\n*..\n",
+        "Rover 1, step 2/2\n\n...\n>#.\n*..\n",
+        "Rover 2, step 0/1\n\n..v\n>#.\n*..\n",
+        "Rover 2, step 1/1\n\n..*\n>#v\n*..\n",
+    ];
+    assert_eq!(frames, expected_output);
+}
+
+#[test]
+fn test_replay_ends_with_failure() {
+    let frames = frames_for(vec![rover_at(0, 0, 'N', "MMM")]);
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(
+        frames[2],
+        "Rover 1, step 2/2\n\n^..\n*#.\n*..\n\nRover 1 stopped: OutOfBounds\n"
+    );
+}
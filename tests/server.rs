@@ -0,0 +1,160 @@
+use rusty_rover::server::{handle_request, read_request, HttpRequest, HttpResponse};
+
+fn request(method: &str, path: &str, content_type: Option<&str>, body: &str) -> HttpRequest {
+    HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        content_type: content_type.map(|content_type| content_type.to_string()),
+        body: body.to_string(),
+    }
+}
+
+#[test]
+fn test_health() {
+    let response = handle_request(&request("GET", "/health", None, ""));
+
+    let expected_response = HttpResponse {
+        status: 200,
+        body: "{\"status\":\"ok\"}".to_string(),
+    };
+    assert_eq!(response, expected_response);
+}
+
+#[test]
+fn test_simulate_text_and_json_missions() {
+    let text_mission = "5 5\n1 2 N\nLMLMLMLMM\n3 3 E\nMMMMMM\n";
+    let json_mission = r#"{"plateau": [5, 5], "rovers": [
+        {"x": 1, "y": 2, "heading": "N", "program": "LMLMLMLMM"},
+        {"x": 3, "y": 3, "heading": "E", "program": "MMMMMM"}
+    ]}"#;
+
+    let expected_body = "{\"rovers\":[\
+        {\"index\":1,\"x\":1,\"y\":3,\"heading\":\"N\",\"status\":\"ok\"},\
+        {\"index\":2,\"x\":null,\"y\":null,\"heading\":null,\"status\":\"OutOfBounds\"}]}";
+    for (content_type, mission) in [
+        (Some("text/plain"), text_mission),
+        (Some("application/json"), json_mission),
+        (None, json_mission),
+    ] {
+        let response = handle_request(&request("POST", "/simulate", content_type, mission));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, expected_body);
+    }
+}
+
+#[test]
+fn test_simulate_invalid_mission() {
+    let response = handle_request(&request("POST", "/simulate", None, "5\n1 2 N\nM\n"));
+
+    let expected_response = HttpResponse {
+        status: 400,
+        body: "{\"error\":{\"name\":\"InvalidPlateau\",\"detail\":\"the plateau coordinates are invalid\"}}".to_string(),
+    };
+    assert_eq!(response, expected_response);
+}
+
+#[test]
+fn test_unknown_path_and_method() {
+    assert_eq!(
+        handle_request(&request("GET", "/rovers", None, "")).status,
+        404
+    );
+    assert_eq!(
+        handle_request(&request("GET", "/simulate", None, "")).status,
+        405
+    );
+}
+
+fn read(raw: &[u8]) -> Result<HttpRequest, HttpResponse> {
+    read_request(&mut &raw[..])
+}
+
+fn bad_request(detail: &str) -> HttpResponse {
+    HttpResponse {
+        status: 400,
+        body: format!(
+            "{{\"error\":{{\"name\":\"BadRequest\",\"detail\":\"{}\"}}}}",
+            detail
+        ),
+    }
+}
+
+#[test]
+fn test_read_request() {
+    let raw = b"POST /simulate HTTP/1.1\r\nHost: localhost\r\nContent-Type: Text/Plain\r\nContent-Length: 10\r\n\r\n5 5\n1 2 N\nM\n";
+
+    // Only as much of the body as its length is read
+    assert_eq!(
+        read(raw),
+        Ok(request(
+            "POST",
+            "/simulate",
+            Some("text/plain"),
+            "5 5\n1 2 N\n"
+        ))
+    );
+    assert_eq!(
+        read(b"GET /health HTTP/1.1\r\n\r\n"),
+        Ok(request("GET", "/health", None, ""))
+    );
+}
+
+#[test]
+fn test_read_request_errors() {
+    assert_eq!(read(b""), Err(bad_request("the request is empty")));
+    assert_eq!(
+        read(b"GET /health\r\n\r\n"),
+        Err(bad_request("the request line is invalid"))
+    );
+    assert_eq!(
+        read(b"GET /health HTTP/1.1\r\nHost: localhost\r\n"),
+        Err(bad_request("the headers are incomplete"))
+    );
+    assert_eq!(
+        read(b"POST /simulate HTTP/1.1\r\nContent-Length: ten\r\n\r\n"),
+        Err(bad_request("the content length is invalid"))
+    );
+    assert_eq!(
+        read(b"POST /simulate HTTP/1.1\r\nContent-Length: 10\r\n\r\n5 5\n"),
+        Err(bad_request("the body is shorter than its content length"))
+    );
+    assert_eq!(
+        read(b"POST /simulate HTTP/1.1\r\nContent-Length: 2\r\n\r\n\xff\xfe"),
+        Err(bad_request("the body isn't UTF-8"))
+    );
+    assert_eq!(
+        read(b"GET /\xff HTTP/1.1\r\n\r\n"),
+        Err(bad_request("the request isn't UTF-8"))
+    );
+    assert_eq!(
+        read(b"POST /simulate HTTP/1.1\r\nContent-Length: 2000000\r\n\r\n")
+            .unwrap_err()
+            .status,
+        413
+    );
+
+    // Chunked bodies aren't read, rather than being taken as empty missions
+    let chunked =
+        read(b"POST /simulate HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n5 5\r\n0\r\n\r\n");
+    assert_eq!(chunked.unwrap_err().status, 411);
+}
+
+#[test]
+fn test_simulate_deeply_nested_program() {
+    // Too deep to parse safely, so refused rather than overflowing the stack
+    let mission = format!("5 5\n1 2 N\n{}\n", "(".repeat(200_000));
+    let response = handle_request(&request("POST", "/simulate", None, &mission));
+
+    assert_eq!(response.status, 400);
+    assert!(response.body.contains("\"name\":\"ProgramTooLong\""));
+
+    // Macros calling each other nest the same way
+    let macros = (0..10_000)
+        .map(|index| format!("def A{} = @A{}\n", index, index + 1))
+        .collect::<String>();
+    let mission = format!("{}def A10000 = M\n5 5\n1 2 N\n@A0\n", macros);
+    let response = handle_request(&request("POST", "/simulate", None, &mission));
+
+    assert_eq!(response.status, 400);
+    assert!(response.body.contains("\"name\":\"ProgramTooLong\""));
+}
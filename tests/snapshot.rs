@@ -0,0 +1,250 @@
+use rusty_rover::command::CommandRegistry;
+use rusty_rover::rover::{
+    simulate_rovers_until_failure, simulate_rovers_with_options, InputCommand, MissionOptions,
+    PositionAndHeading, RoverError, RoverProfile, RoverReport, Topology, NORTH_EAST,
+};
+use rusty_rover::snapshot::{
+    format_snapshot, format_snapshot_json, parse_snapshot, parse_snapshot_json, resume_snapshot,
+    take_snapshot, RoverSnapshot, RoverStatus, Snapshot,
+};
+
+fn rover_at(x: i32, y: i32, heading: char, program: &str) -> (PositionAndHeading, String) {
+    (PositionAndHeading { x, y, heading }, program.to_string())
+}
+
+fn snapshot_rover(
+    x: i32,
+    y: i32,
+    heading: char,
+    program: &str,
+    status: RoverStatus,
+) -> RoverSnapshot {
+    RoverSnapshot {
+        position: rover_at(x, y, heading, "").0,
+        program: program.to_string(),
+        status,
+    }
+}
+
+fn example_mission(second_program: &str) -> (InputCommand, MissionOptions) {
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            rover_at(1, 2, 'N', "LMLMLMLMM"),
+            rover_at(3, 3, 'E', second_program),
+            rover_at(0, 0, 'N', "2M"),
+        ],
+    };
+    let options = MissionOptions {
+        obstacles: vec![(2, 2)],
+        ..MissionOptions::default()
+    };
+    (input_command, options)
+}
+
+#[test]
+fn test_snapshot_part_way_through_mission() {
+    let (input_command, options) = example_mission("MMRMMRMRRM");
+
+    let snapshot = take_snapshot(input_command.clone(), &options, 12).unwrap();
+    let expected_output = Snapshot {
+        ur_plateau: (5, 5),
+        options: MissionOptions {
+            obstacles: vec![(2, 2)],
+            ..MissionOptions::default()
+        },
+        rovers: vec![
+            snapshot_rover(1, 3, 'N', "", RoverStatus::Finished),
+            snapshot_rover(5, 3, 'S', "MMRMRRM", RoverStatus::Active),
+            snapshot_rover(0, 0, 'N', "2M", RoverStatus::Pending),
+        ],
+    };
+    assert_eq!(snapshot, expected_output);
+
+    // Resuming finishes the mission the same as running it from the start
+    let resumed = resume_snapshot(&snapshot, &CommandRegistry::new()).unwrap();
+    let full_run = simulate_rovers_with_options(input_command, &options).unwrap();
+    let final_positions = |reports: &[RoverReport]| {
+        reports
+            .iter()
+            .map(|report| report.trajectory[report.trajectory.len() - 1].clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(resumed.failure, None);
+    assert_eq!(
+        final_positions(&resumed.reports),
+        final_positions(&full_run)
+    );
+}
+
+#[test]
+fn test_snapshot_before_failing_step() {
+    let (input_command, options) = example_mission("MMMMMM");
+
+    let snapshot = take_snapshot(input_command, &options, usize::MAX).unwrap();
+    assert_eq!(
+        snapshot.rovers[1],
+        snapshot_rover(
+            5,
+            3,
+            'E',
+            "MMMM",
+            RoverStatus::Failed("OutOfBounds".to_string())
+        )
+    );
+    assert_eq!(snapshot.rovers[2].status, RoverStatus::Pending);
+
+    // The failing step can be reproduced straight from the snapshot
+    let resumed = resume_snapshot(&snapshot, &CommandRegistry::new()).unwrap();
+    let failure = resumed.failure.unwrap();
+    assert_eq!((failure.rover, failure.error), (1, RoverError::OutOfBounds));
+    assert_eq!(failure.report.trajectory.len(), 1);
+}
+
+#[test]
+fn test_snapshot_text_and_json_round_trip() {
+    let (input_command, options) = example_mission("MMMMMM");
+    let snapshot = take_snapshot(input_command, &options, usize::MAX).unwrap();
+
+    let text = format_snapshot(&snapshot);
+    assert_eq!(
+        text,
+        "plateau 5 5\nobstacle 2 2\nrover 1 3 N finished\nrover 5 3 E failed OutOfBounds MMMM\nrover 0 0 N pending 2M\n"
+    );
+    assert_eq!(parse_snapshot(&text), Ok(snapshot.clone()));
+    assert_eq!(
+        parse_snapshot_json(&format_snapshot_json(&snapshot)),
+        Ok(snapshot)
+    );
+}
+
+#[test]
+fn test_snapshot_keeps_topology() {
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(1, 1, 'E', "MLM")],
+    };
+    let options = MissionOptions {
+        topology: Topology::Hex,
+        ..MissionOptions::default()
+    };
+
+    let snapshot = take_snapshot(input_command, &options, 1).unwrap();
+    let text = format_snapshot(&snapshot);
+    assert_eq!(text, "plateau 5 5\ntopology hex\nrover 2 1 E active LM\n");
+
+    let resumed = resume_snapshot(&parse_snapshot(&text).unwrap(), &CommandRegistry::new());
+    let report = &resumed.unwrap().reports[0];
+    assert_eq!(
+        report.trajectory[report.trajectory.len() - 1],
+        rover_at(2, 2, NORTH_EAST, "").0
+    );
+}
+
+#[test]
+fn test_snapshot_keeps_rover_types_and_samples() {
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, 'N', "MSMSMM")],
+    };
+    let options = MissionOptions {
+        rover_profiles: vec![RoverProfile {
+            battery: Some(3),
+            payload: Some(2),
+            ..RoverProfile::default()
+        }],
+        sample_sites: vec![(0, 1), (0, 2)],
+        ..MissionOptions::default()
+    };
+
+    // The rover has used two units of battery and collected the first sample
+    let snapshot = take_snapshot(input_command.clone(), &options, 3).unwrap();
+    let text = format_snapshot(&snapshot);
+    assert_eq!(
+        text,
+        "plateau 5 5\nsample 0 2\nrover 0 2 N active SMM\ntype battery=1 payload=1\n"
+    );
+    assert_eq!(parse_snapshot(&text), Ok(snapshot.clone()));
+    assert_eq!(
+        parse_snapshot_json(&format_snapshot_json(&snapshot)),
+        Ok(snapshot.clone())
+    );
+
+    // Resuming runs the battery flat on the same command as the full run
+    let resumed = resume_snapshot(&snapshot, &CommandRegistry::new()).unwrap();
+    let full_run = simulate_rovers_until_failure(input_command, &options).unwrap();
+    let failure = resumed.failure.unwrap();
+    let full_failure = full_run.failure.unwrap();
+    assert_eq!(failure.error, RoverError::BatteryDepleted);
+    assert_eq!(failure.error, full_failure.error);
+    assert_eq!(
+        failure.report.trajectory.last(),
+        full_failure.report.trajectory.last()
+    );
+}
+
+#[test]
+fn test_snapshot_parse_errors() {
+    assert_eq!(
+        parse_snapshot("plateau 5 5\nrover 1 2 N lost M\n"),
+        Err(RoverError::InvalidOption { line: 2 })
+    );
+    assert_eq!(
+        parse_snapshot("plateau 5 5\n"),
+        Err(RoverError::InvalidNumberOfCommandsForRover)
+    );
+    assert_eq!(
+        parse_snapshot("plateau 5 5\nrover 1 2 N active M\ntype speed=0\n"),
+        Err(RoverError::InvalidOption { line: 3 })
+    );
+    assert_eq!(
+        parse_snapshot("plateau 5 5\nrover 1 2 N active M\nobstacle 9 9\n"),
+        Err(RoverError::InvalidObstacle { line: 3 })
+    );
+    assert_eq!(
+        parse_snapshot_json(
+            r#"{"plateau": [5, 5], "rovers": [{"x": 1, "y": 2, "heading": "N", "status": "failed", "program": ""}]}"#
+        ),
+        Err(RoverError::InvalidJson(
+            "'rovers[0].status' is invalid".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_snapshot_passing_over_parked_rover() {
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(1, 1, 'N', ""), rover_at(1, 0, 'N', "MM")],
+    };
+    let options = MissionOptions::default();
+
+    // Rovers only collide where they finish, so the second rover is just passing over
+    let snapshot = take_snapshot(input_command.clone(), &options, 1).unwrap();
+    assert_eq!(
+        snapshot.rovers[1],
+        snapshot_rover(1, 1, 'N', "M", RoverStatus::Active)
+    );
+    assert_eq!(
+        resume_snapshot(&snapshot, &CommandRegistry::new())
+            .unwrap()
+            .failure,
+        None
+    );
+
+    // Finishing on the parked rover is still caught, after its last command
+    let input_command = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(1, 1, 'N', ""), rover_at(1, 0, 'N', "M")],
+    };
+    let snapshot = take_snapshot(input_command, &options, 5).unwrap();
+    assert_eq!(
+        snapshot.rovers[1],
+        snapshot_rover(1, 1, 'N', "", RoverStatus::Failed("Collision".to_string()))
+    );
+    let failure = resume_snapshot(&snapshot, &CommandRegistry::new())
+        .unwrap()
+        .failure
+        .unwrap();
+    assert_eq!((failure.rover, failure.error), (1, RoverError::Collision));
+}
@@ -0,0 +1,63 @@
+use rusty_rover::rover::{
+    simulate_rovers_until_failure, InputCommand, MissionOptions, PositionAndHeading,
+};
+use rusty_rover::svg::render_svg;
+
+fn rover_at(x: i32, y: i32, heading: char, program: &str) -> (PositionAndHeading, String) {
+    (PositionAndHeading { x, y, heading }, program.to_string())
+}
+
+fn render(rovers_to_deploy: Vec<(PositionAndHeading, String)>, obstacles: &[(i32, i32)]) -> String {
+    let input_command = InputCommand {
+        ur_plateau: (2, 2),
+        rovers_to_deploy,
+    };
+    let options = MissionOptions {
+        obstacles: obstacles.to_vec(),
+        ..MissionOptions::default()
+    };
+    let result = simulate_rovers_until_failure(input_command, &options).unwrap();
+    render_svg((2, 2), obstacles, &result)
+}
+
+#[test]
+fn test_svg_draws_grid_and_trajectories() {
+    let svg = render(
+        vec![rover_at(0, 0, 'N', "MR"), rover_at(2, 2, 'S', "M")],
+        &[(1, 1)],
+    );
+
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"200\"")
+    );
+    assert!(svg.ends_with("</svg>\n"));
+    // Cells are 40 pixels with north at the top, so (0, 0) is centered at 60,140
+    assert!(svg.contains("<rect x=\"80\" y=\"80\" width=\"40\" height=\"40\" fill=\"#666\"/>"));
+    assert!(
+        svg.contains("<polyline points=\"60,140 60,100 60,100\" fill=\"none\" stroke=\"#1f77b4\"")
+    );
+    assert!(svg.contains("<polyline points=\"140,60 140,100\" fill=\"none\" stroke=\"#ff7f0e\""));
+    assert!(svg.contains("transform=\"rotate(90 60 100)\""));
+    assert!(!svg.contains("#d62728"));
+}
+
+#[test]
+fn test_svg_highlights_failure() {
+    let svg = render(vec![rover_at(0, 0, 'E', "MM")], &[(2, 0)]);
+
+    // The obstacle the rover was stopped from driving into is ringed
+    assert!(svg.contains("<polyline points=\"60,140 100,140\""));
+    assert!(svg.contains(
+        "<circle cx=\"140\" cy=\"140\" r=\"16\" fill=\"none\" stroke=\"#d62728\" stroke-width=\"4\"><title>Collision</title></circle>"
+    ));
+}
+
+#[test]
+fn test_svg_highlights_cell_off_the_plateau() {
+    let svg = render(vec![rover_at(0, 0, 'W', "M")], &[]);
+
+    // The cell West of (0, 0) is in the margin, left of the plateau
+    assert!(svg.contains(
+        "<circle cx=\"20\" cy=\"140\" r=\"16\" fill=\"none\" stroke=\"#d62728\" stroke-width=\"4\"><title>OutOfBounds</title></circle>"
+    ));
+}
@@ -1,5 +1,8 @@
 use rusty_rover::rover::{
-    parse_user_plateau, simulate_rover_move, InputCommand, PositionAndHeading, RoverError,
+    heading_name, parse_rover_to_deploy, parse_user_plateau, simulate_rover_move,
+    simulate_rover_move_with_options, simulate_rovers_until_failure, InputCommand, MissionOptions,
+    PositionAndHeading, RoverError, RoverProfile, Topology, NORTH_EAST, NORTH_WEST, SOUTH_EAST,
+    SOUTH_WEST,
 };
 
 #[test]
@@ -291,7 +294,7 @@ fn test_bad_command_move_handle_extra_non_alphanumerics() {
                 y: 2,
                 heading: 'N',
             },
-            "LMLM%LM4LM'M".to_string(),
+            "LMLM%LM&LM'M".to_string(),
         )],
     };
 
@@ -413,3 +416,726 @@ fn test_parse_user_plateau_special_chars() {
     let expected_output = Ok((5, 5));
     assert_eq!(parse_user_plateau(test_input), expected_output);
 }
+
+#[test]
+fn test_command_move_with_counts() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: 'N',
+            },
+            "5M 3L 2M".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 2,
+        y: 5,
+        heading: 'E',
+    }];
+    assert_eq!(simulate_rover_move(test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_command_move_with_repeated_group() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: 'N',
+            },
+            "(LM)4M".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 1,
+        y: 3,
+        heading: 'N',
+    }];
+    assert_eq!(simulate_rover_move(test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_bad_command_move_unbalanced_group() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: 'N',
+            },
+            "(MMR4".to_string(),
+        )],
+    };
+
+    let expected_output = Err(RoverError::InvalidMove);
+    assert_eq!(simulate_rover_move(test_input), expected_output);
+}
+
+#[test]
+fn test_command_drive_to_edge() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: 'E',
+            },
+            "while !edge { M } L".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 5,
+        y: 2,
+        heading: 'N',
+    }];
+    assert_eq!(simulate_rover_move(test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_command_if_blocked_by_rover() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            (
+                PositionAndHeading {
+                    x: 1,
+                    y: 3,
+                    heading: 'N',
+                },
+                "".to_string(),
+            ),
+            (
+                PositionAndHeading {
+                    x: 1,
+                    y: 2,
+                    heading: 'N',
+                },
+                "IF BLOCKED { R M } ELSE { M }".to_string(),
+            ),
+        ],
+    };
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 3,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 2,
+            y: 2,
+            heading: 'E',
+        },
+    ];
+    assert_eq!(simulate_rover_move(test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_driving_through_rover_only_checks_final_position() {
+    let through_rover = || InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(1, 3, 'N', ""), rover_at(1, 2, 'N', "MM")],
+    };
+    let path_collisions = MissionOptions {
+        path_collisions: true,
+        ..MissionOptions::default()
+    };
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 3,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 1,
+            y: 4,
+            heading: 'N',
+        },
+    ];
+    assert_eq!(simulate_rover_move(through_rover()), Ok(expected_output));
+    assert_eq!(
+        simulate_rover_move_with_options(through_rover(), &path_collisions),
+        Err(RoverError::Collision)
+    );
+}
+
+#[test]
+fn test_command_endless_loop_hits_step_limit() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: 'N',
+            },
+            "while !blocked { R }".to_string(),
+        )],
+    };
+
+    let expected_output = Err(RoverError::StepLimitExceeded);
+    assert_eq!(simulate_rover_move(test_input), expected_output);
+}
+
+fn diagonal_options(obstacles: Vec<(i32, i32)>) -> MissionOptions {
+    MissionOptions {
+        obstacles,
+        rover_profiles: vec![
+            RoverProfile {
+                diagonal: true,
+                ..RoverProfile::default()
+            };
+            2
+        ],
+        ..MissionOptions::default()
+    }
+}
+
+#[test]
+fn test_diagonal_heading_needs_diagonal_rover() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: NORTH_EAST,
+            },
+            "M".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 2,
+        y: 3,
+        heading: NORTH_EAST,
+    }];
+    assert_eq!(
+        simulate_rover_move(test_input.clone()),
+        Err(RoverError::InvalidHeading)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &diagonal_options(Vec::new())),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_diagonal_turns_and_moves() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: 'N',
+            },
+            "CMM AM RM ACM LCM".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 5,
+        y: 4,
+        heading: NORTH_EAST,
+    }];
+    assert_eq!(
+        simulate_rover_move_with_options(test_input.clone(), &MissionOptions::default()),
+        Err(RoverError::InvalidMove)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &diagonal_options(Vec::new())),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_diagonal_move_out_of_bounds() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 5,
+                y: 2,
+                heading: SOUTH_EAST,
+            },
+            "M".to_string(),
+        )],
+    };
+
+    let expected_output = Err(RoverError::OutOfBounds);
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &diagonal_options(Vec::new())),
+        expected_output
+    );
+}
+
+#[test]
+fn test_diagonal_move_cannot_cut_corners() {
+    let past_obstacle = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 1,
+                heading: NORTH_EAST,
+            },
+            "M".to_string(),
+        )],
+    };
+    let past_rover = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            (
+                PositionAndHeading {
+                    x: 1,
+                    y: 2,
+                    heading: 'N',
+                },
+                "".to_string(),
+            ),
+            (
+                PositionAndHeading {
+                    x: 1,
+                    y: 1,
+                    heading: NORTH_EAST,
+                },
+                "if blocked { R } else { M }".to_string(),
+            ),
+        ],
+    };
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 1,
+            y: 2,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 1,
+            y: 1,
+            heading: SOUTH_EAST,
+        },
+    ];
+    assert_eq!(
+        simulate_rover_move_with_options(past_obstacle, &diagonal_options(vec![(2, 1)])),
+        Err(RoverError::Collision)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(past_rover, &diagonal_options(Vec::new())),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_parse_diagonal_start_heading() {
+    let start_lines = |start: &str| vec!["M".to_string(), start.to_string()];
+
+    let expected_output = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 1,
+                y: 2,
+                heading: NORTH_EAST,
+            },
+            "M".to_string(),
+        )],
+    };
+    assert_eq!(
+        parse_rover_to_deploy((5, 5), start_lines("1 2 ne")),
+        Ok(expected_output)
+    );
+    assert_eq!(heading_name(NORTH_EAST), "NE");
+    assert_eq!(
+        parse_rover_to_deploy((5, 5), start_lines("1 2 N X")),
+        Err(RoverError::InvalidStartPosition)
+    );
+}
+
+fn hex_options() -> MissionOptions {
+    MissionOptions {
+        topology: Topology::Hex,
+        ..MissionOptions::default()
+    }
+}
+
+#[test]
+fn test_hex_turns_and_moves() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 2,
+                y: 2,
+                heading: 'E',
+            },
+            "M LM LM LM LM LM".to_string(),
+        )],
+    };
+
+    // Six moves turning 60 degrees between each drive round a single hex
+    let expected_output = vec![PositionAndHeading {
+        x: 2,
+        y: 2,
+        heading: SOUTH_EAST,
+    }];
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &hex_options()),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_hex_headings() {
+    let square_heading = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 2,
+                y: 2,
+                heading: 'N',
+            },
+            "M".to_string(),
+        )],
+    };
+    let hex_heading = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 2,
+                y: 2,
+                heading: NORTH_WEST,
+            },
+            "MRRRM".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 2,
+        y: 2,
+        heading: SOUTH_EAST,
+    }];
+    assert_eq!(
+        simulate_rover_move_with_options(square_heading, &hex_options()),
+        Err(RoverError::InvalidHeading)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(hex_heading.clone(), &hex_options()),
+        Ok(expected_output)
+    );
+    assert_eq!(
+        simulate_rover_move(hex_heading),
+        Err(RoverError::InvalidHeading)
+    );
+}
+
+#[test]
+fn test_hex_out_of_bounds_and_blocked() {
+    let out_of_bounds = InputCommand {
+        ur_plateau: (3, 3),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 3,
+                y: 0,
+                heading: SOUTH_EAST,
+            },
+            "M".to_string(),
+        )],
+    };
+    let around_obstacle = InputCommand {
+        ur_plateau: (3, 3),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: NORTH_EAST,
+            },
+            "while !edge { if blocked { R } else { M } }".to_string(),
+        )],
+    };
+
+    let mut options = hex_options();
+    options.obstacles.push((0, 2));
+    let expected_output = vec![PositionAndHeading {
+        x: 3,
+        y: 1,
+        heading: 'E',
+    }];
+    assert_eq!(
+        simulate_rover_move_with_options(out_of_bounds, &hex_options()),
+        Err(RoverError::OutOfBounds)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(around_obstacle, &options),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_reverse_and_u_turn() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            (
+                PositionAndHeading {
+                    x: 2,
+                    y: 2,
+                    heading: 'N',
+                },
+                "BUM".to_string(),
+            ),
+            (
+                PositionAndHeading {
+                    x: 4,
+                    y: 4,
+                    heading: 'E',
+                },
+                "bRb".to_string(),
+            ),
+        ],
+    };
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 2,
+            y: 0,
+            heading: 'S',
+        },
+        PositionAndHeading {
+            x: 3,
+            y: 5,
+            heading: 'S',
+        },
+    ];
+    assert_eq!(simulate_rover_move(test_input), Ok(expected_output));
+}
+
+#[test]
+fn test_reverse_checks_bounds_and_collisions() {
+    let out_of_bounds = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 0,
+                y: 0,
+                heading: 'N',
+            },
+            "B".to_string(),
+        )],
+    };
+    let into_rover = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![
+            (
+                PositionAndHeading {
+                    x: 1,
+                    y: 1,
+                    heading: 'N',
+                },
+                "".to_string(),
+            ),
+            (
+                PositionAndHeading {
+                    x: 3,
+                    y: 1,
+                    heading: 'E',
+                },
+                "BB".to_string(),
+            ),
+        ],
+    };
+
+    assert_eq!(
+        simulate_rover_move(out_of_bounds),
+        Err(RoverError::OutOfBounds)
+    );
+    assert_eq!(simulate_rover_move(into_rover), Err(RoverError::Collision));
+}
+
+#[test]
+fn test_hex_reverse_and_u_turn() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![(
+            PositionAndHeading {
+                x: 2,
+                y: 2,
+                heading: NORTH_EAST,
+            },
+            "BU".to_string(),
+        )],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 2,
+        y: 1,
+        heading: SOUTH_WEST,
+    }];
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &hex_options()),
+        Ok(expected_output)
+    );
+}
+
+fn large_rover_options(rover: usize) -> MissionOptions {
+    let mut rover_profiles = vec![RoverProfile::default(); rover + 1];
+    rover_profiles[rover].footprint = (2, 2);
+    MissionOptions {
+        rover_profiles,
+        ..MissionOptions::default()
+    }
+}
+
+fn rover_at(x: i32, y: i32, heading: char, program: &str) -> (PositionAndHeading, String) {
+    (PositionAndHeading { x, y, heading }, program.to_string())
+}
+
+#[test]
+fn test_large_rover_bounds() {
+    let drives_to_edge = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, 'N', "MMMM")],
+    };
+    let drives_off_edge = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, 'N', "MMMMM")],
+    };
+    let turns_off_edge = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, 'N', "R")],
+    };
+    let starts_off_edge = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(5, 5, 'N', "")],
+    };
+    let stops_at_edge = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, 'N', "while !edge { M }")],
+    };
+
+    let expected_output = vec![PositionAndHeading {
+        x: 0,
+        y: 4,
+        heading: 'N',
+    }];
+    let options = large_rover_options(0);
+    assert_eq!(
+        simulate_rover_move_with_options(drives_to_edge, &options),
+        Ok(expected_output.clone())
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(drives_off_edge, &options),
+        Err(RoverError::OutOfBounds)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(turns_off_edge, &options),
+        Err(RoverError::OutOfBounds)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(starts_off_edge, &options),
+        Err(RoverError::StartOutOfBounds)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(stops_at_edge, &options),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_large_rover_collisions() {
+    let drives_into_rover = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(1, 3, 'N', ""), rover_at(0, 0, 'N', "MM")],
+    };
+    let parked_on_rover = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, 'N', ""), rover_at(1, 1, 'N', "")],
+    };
+    let turns_into_rover = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(2, 1, 'N', ""), rover_at(1, 2, 'N', "R")],
+    };
+    let turns_clear = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(2, 1, 'N', ""), rover_at(1, 2, 'N', "L")],
+    };
+
+    let expected_output = vec![
+        PositionAndHeading {
+            x: 2,
+            y: 1,
+            heading: 'N',
+        },
+        PositionAndHeading {
+            x: 1,
+            y: 2,
+            heading: 'W',
+        },
+    ];
+    assert_eq!(
+        simulate_rover_move_with_options(drives_into_rover, &large_rover_options(1)),
+        Err(RoverError::Collision)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(parked_on_rover, &large_rover_options(0)),
+        Err(RoverError::Collision)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(turns_into_rover, &large_rover_options(1)),
+        Err(RoverError::Collision)
+    );
+    assert_eq!(
+        simulate_rover_move_with_options(turns_clear, &large_rover_options(1)),
+        Ok(expected_output)
+    );
+}
+
+#[test]
+fn test_large_rover_needs_square_turns() {
+    let test_input = InputCommand {
+        ur_plateau: (5, 5),
+        rovers_to_deploy: vec![rover_at(0, 0, NORTH_EAST, "")],
+    };
+
+    let mut options = large_rover_options(0);
+    options.topology = Topology::Hex;
+    assert_eq!(
+        simulate_rover_move_with_options(test_input, &options),
+        Err(RoverError::InvalidFootprint)
+    );
+}
+
+#[test]
+fn test_simulate_until_failure_keeps_partial_trajectory() {
+    let test_input = InputCommand {
+        ur_plateau: (2, 2),
+        rovers_to_deploy: vec![
+            rover_at(0, 0, 'N', "M"),
+            rover_at(1, 1, 'E', "MMM"),
+            rover_at(2, 0, 'N', "M"),
+        ],
+    };
+
+    let result = simulate_rovers_until_failure(test_input, &MissionOptions::default()).unwrap();
+    assert_eq!(result.reports.len(), 1);
+
+    let failure = result.failure.unwrap();
+    assert_eq!(failure.rover, 1);
+    assert_eq!(failure.error, RoverError::OutOfBounds);
+    assert_eq!(
+        failure.report.trajectory,
+        vec![rover_at(1, 1, 'E', "").0, rover_at(2, 1, 'E', "").0]
+    );
+}